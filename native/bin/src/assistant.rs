@@ -0,0 +1,209 @@
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use we_core::algo::{check, get_consistent_answers, get_valid_hints, update_knowledge};
+use we_core::calibration::Model;
+use we_core::entropy::{entropies_scored, guess_summaries, uncertainty};
+use we_core::filter::get_guesses;
+use we_core::structs::{knowledge::KnowledgeN, Dictionary, HintsN, WordN};
+use wordle_entropy_core as we_core;
+
+use crate::Result;
+
+const HELP: &str = "\
+Commands:
+  <guess> <hints>  add the guess played along with its hints, e.g. `crane ccowo`
+                   (c - correct, o - out of place, w - wrong)
+  undo             remove the last guess
+  answers          list all the possible answers
+  help             print this message
+  quit             exit";
+
+/// Number of the possible answers listed after each step
+const ANSWERS_SHOWN: usize = 10;
+
+struct Assistant<'a, const N: usize> {
+    dictionary: &'a Dictionary<N>,
+    hard_mode: bool,
+    calibration: Model,
+    top: usize,
+    history: Vec<(WordN<char, N>, HintsN<N>)>,
+}
+
+impl<'a, const N: usize> Assistant<'a, N> {
+    fn knowledge(&self) -> KnowledgeN<N> {
+        self.history
+            .iter()
+            .fold(KnowledgeN::none(), |knowledge, (guess, hints)| {
+                update_knowledge(guess, hints, knowledge)
+            })
+    }
+
+    fn answers(&self) -> Vec<usize> {
+        get_consistent_answers(
+            &self.dictionary.words,
+            &self.dictionary.answers,
+            &self.history,
+        )
+    }
+
+    fn print_answers(&self, answers: &[usize], limit: usize) {
+        let probabilities = &self.dictionary.probabilities;
+        let prob_norm: f64 = answers.iter().map(|&i| probabilities[i]).sum();
+
+        let mut answers = answers.to_vec();
+        answers.sort_by(|&i, &j| probabilities[j].total_cmp(&probabilities[i]));
+        for &i in answers.iter().take(limit) {
+            println!(
+                "  {}: {:.3}",
+                self.dictionary.words[i],
+                probabilities[i] / prob_norm
+            );
+        }
+        if answers.len() > limit {
+            println!("  ... and {} more", answers.len() - limit);
+        }
+    }
+
+    fn print_state(&self) {
+        for (guess, hints) in &self.history {
+            println!("{guess} {hints}");
+        }
+
+        let answers = self.answers();
+        let uncertainty = uncertainty(self.dictionary, &answers);
+        println!(
+            "{} possible answers, uncertainty: {uncertainty:.3} bits",
+            answers.len()
+        );
+        self.print_answers(&answers, ANSWERS_SHOWN);
+
+        if let &[answer] = &answers[..] {
+            println!("The answer is {}", self.dictionary.words[answer]);
+            return;
+        }
+
+        let knowledge = self.knowledge();
+        let guesses = get_guesses(self.dictionary, &knowledge, self.hard_mode);
+        let entropies = guess_summaries(self.dictionary, None, &guesses, &answers);
+        let scores = entropies_scored(
+            self.dictionary,
+            &answers,
+            entropies,
+            Some(uncertainty),
+            Some(&self.calibration),
+        );
+
+        println!("Suggested guesses (entropy, expected turns):");
+        for (i, entropies_data, turns) in scores.into_iter().take(self.top) {
+            println!(
+                "  {}: {:.3}, {turns:.3}",
+                self.dictionary.words[i], entropies_data.entropy
+            );
+        }
+    }
+
+    /// Validates the guess with its hints against the hints revealed so far and adds it
+    fn add_step(&mut self, guess: &str, hints: &str) -> Result<()> {
+        let guess = WordN::<char, N>::try_from(guess.to_lowercase().as_str())?;
+        let hints = HintsN::<N>::from_str(hints)
+            .map_err(|err| format!("Invalid hints \"{hints}\": {err}"))?;
+
+        if !self.dictionary.words.contains(&guess) {
+            return Err(format!("{guess} is not in the word list").into());
+        }
+
+        let knowledge = self.knowledge();
+        if self.hard_mode && !check(&guess, &knowledge) {
+            return Err(format!(
+                "{guess} ignores some of the revealed hints, not allowed in hard mode"
+            )
+            .into());
+        }
+
+        let valid_hints = get_valid_hints(&guess, &hints, &knowledge);
+        if let Some(i) = (0..N).find(|&i| !valid_hints.0[i].contains(&hints.0[i])) {
+            return Err(format!(
+                "Hint {} for letter {} of {guess} contradicts the previous hints",
+                hints.0[i],
+                i + 1
+            )
+            .into());
+        }
+
+        self.history.push((guess, hints));
+        if self.answers().is_empty() {
+            let (guess, hints) = self.history.pop().unwrap();
+            return Err(format!("None of the answers gives {hints} for {guess}").into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Interactive assistant suggesting the guesses for a puzzle solved elsewhere
+pub fn assistant<const N: usize>(
+    dictionary: &Dictionary<N>,
+    hard_mode: bool,
+    calibration: Model,
+    top: usize,
+) -> Result<()> {
+    let mut assistant = Assistant {
+        dictionary,
+        hard_mode,
+        calibration,
+        top,
+        history: vec![],
+    };
+
+    println!("{HELP}");
+    println!();
+    assistant.print_state();
+
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        let args = line.split_whitespace().collect::<Vec<_>>();
+
+        match args[..] {
+            [] => continue,
+            ["quit"] | ["exit"] => break,
+            ["help"] => {
+                println!("{HELP}");
+                continue;
+            }
+            ["answers"] => {
+                let answers = assistant.answers();
+                assistant.print_answers(&answers, answers.len());
+                continue;
+            }
+            ["undo"] => {
+                if assistant.history.pop().is_none() {
+                    println!("Nothing to undo");
+                    continue;
+                }
+            }
+            [guess, hints] => {
+                if let Err(err) = assistant.add_step(guess, hints) {
+                    println!("{err}");
+                    continue;
+                }
+            }
+            _ => {
+                println!("Unknown command, type `help` to see the available ones");
+                continue;
+            }
+        }
+
+        println!();
+        assistant.print_state();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,493 @@
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use rand::prelude::IteratorRandom;
+use we_core::algo::{check, get_consistent_answers, get_hints_and_update, update_knowledge};
+use we_core::benchmark::{self, benchmark, BenchmarkOptions, TurnsStats};
+use we_core::calibration::{
+    bin_data, compare_models, CalibrationFile, Model, ModelComparison, ModelFamily, TurnsModel,
+};
+use we_core::entropy::{
+    entropies_scored, guess_summaries, guess_summaries_with_progress, rank_scores, uncertainty,
+    ScoreKind,
+};
+use we_core::filter::get_guesses;
+use we_core::multi_board::{score_guesses, MultiBoard, MultiBoardOptions};
+use we_core::priors::Prior;
+use we_core::progress::Progress;
+use we_core::solvers::uncertainty_data;
+use we_core::structs::{knowledge::KnowledgeN, Dictionary, EntropiesData, HintsN, WordN};
+use wordle_entropy_core as we_core;
+
+use crate::{plot, Result};
+
+fn parse_word<const N: usize>(word: &str) -> Result<WordN<char, N>> {
+    Ok(WordN::try_from(word.trim().to_lowercase().as_str())?)
+}
+
+/// Parses a step in the `word:hints` format
+fn parse_step<const N: usize>(step: &str) -> Result<(WordN<char, N>, HintsN<N>)> {
+    let (word, hints) = step
+        .split_once(':')
+        .ok_or_else(|| format!("Expected guess in the word:hints format, found \"{step}\""))?;
+    let hints = HintsN::from_str(hints)
+        .map_err(|err| format!("Invalid hints \"{hints}\" for {word}: {err}"))?;
+
+    Ok((parse_word(word)?, hints))
+}
+
+const SCORES_HEADER: &str =
+    "guess, entropy, expected turns, max bucket, expected size, buckets, solve probability";
+
+fn format_scores<const N: usize>(
+    word: &WordN<char, N>,
+    entropies_data: &EntropiesData,
+    turns: f64,
+) -> String {
+    format!(
+        "{word}, {:.3}, {turns:.3}, {}, {:.3}, {}, {:.3}",
+        entropies_data.entropy,
+        entropies_data.max_bucket,
+        entropies_data.expected_size,
+        entropies_data.buckets,
+        entropies_data.solve_probability
+    )
+}
+
+pub fn suggest<const N: usize>(
+    dictionary: &Dictionary<N>,
+    steps: &[String],
+    top: usize,
+    sort: ScoreKind,
+    hard_mode: bool,
+    calibration: Model,
+) -> Result<()> {
+    let history = steps
+        .iter()
+        .map(|step| parse_step(step))
+        .collect::<Result<Vec<_>>>()?;
+
+    let knowledge = history
+        .iter()
+        .fold(KnowledgeN::none(), |knowledge, (guess, hints)| {
+            update_knowledge(guess, hints, knowledge)
+        });
+    let answers = get_consistent_answers(&dictionary.words, &dictionary.answers, &history);
+    if answers.is_empty() {
+        return Err("None of the answers matches the given hints".into());
+    }
+
+    let uncertainty = uncertainty(dictionary, &answers);
+    println!(
+        "{} possible answers, uncertainty: {uncertainty:.3} bits",
+        answers.len()
+    );
+    if answers.len() <= 10 {
+        for &i in &answers {
+            println!("  {}", dictionary.words[i]);
+        }
+    }
+
+    let guesses = get_guesses(dictionary, &knowledge, hard_mode);
+    let entropies = guess_summaries(dictionary, None, &guesses, &answers);
+    let mut scores = entropies_scored(
+        dictionary,
+        &answers,
+        entropies,
+        Some(uncertainty),
+        Some(&calibration),
+    );
+    rank_scores(&mut scores, sort);
+
+    println!("{SCORES_HEADER}");
+    for (i, entropies_data, turns) in scores.iter().take(top) {
+        println!(
+            "{}",
+            format_scores(&dictionary.words[*i], entropies_data, *turns)
+        );
+    }
+
+    Ok(())
+}
+
+/// `samples` random answers, all of them if not given
+fn sample_answers<const N: usize>(
+    dictionary: &Dictionary<N>,
+    samples: Option<usize>,
+) -> Vec<usize> {
+    match samples {
+        Some(n) => dictionary
+            .answers
+            .iter()
+            .copied()
+            .choose_multiple(&mut rand::thread_rng(), n),
+        None => dictionary.answers.clone(),
+    }
+}
+
+pub fn simulate<const N: usize>(
+    dictionary: &Dictionary<N>,
+    samples: Option<usize>,
+    openers: &[String],
+    options: BenchmarkOptions,
+    output: Option<PathBuf>,
+    json: Option<PathBuf>,
+) -> Result<()> {
+    let answers = sample_answers(dictionary, samples);
+    let openers = openers
+        .iter()
+        .map(|opener| {
+            let opener = parse_word(opener)?;
+            dictionary
+                .words
+                .iter()
+                .position(|w| *w == opener)
+                .ok_or_else(|| format!("{opener} is not in the word list").into())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let options = BenchmarkOptions { openers, ..options };
+    let report = benchmark(dictionary, &answers, &options, &progress_bar("Benchmark"))?;
+
+    let print_stats = |stats: &TurnsStats| {
+        println!("games: {}", stats.games);
+        println!("mean turns: {:.3}", stats.mean_turns);
+        println!("max turns: {}", stats.max_turns);
+        println!("failures: {}", stats.failures);
+        for n in 1..=stats.max_turns {
+            let count = stats.histogram.get(&n).copied().unwrap_or_default();
+            println!("{n}: {count:>6} {}", "#".repeat(count * 50 / stats.games));
+        }
+    };
+
+    println!("strategy: {}", report.strategy);
+    print_stats(&report.stats);
+    if report.openers.len() > 1 {
+        for (opener, stats) in &report.openers {
+            println!();
+            println!("opener {opener}");
+            print_stats(stats);
+        }
+    }
+    println!();
+    println!("worst answers:");
+    for result in &report.worst {
+        println!("  {}: {}", result.answer, result.guesses.join(" "));
+    }
+
+    if let Some(output) = output {
+        std::fs::write(&output, report.to_csv())?;
+        println!("Turns saved to {}", output.display());
+    }
+    if let Some(json) = json {
+        serde_json::to_writer_pretty(File::create(&json)?, &report)?;
+        println!("Report saved to {}", json.display());
+    }
+
+    Ok(())
+}
+
+pub fn compare_priors<const N: usize>(
+    dictionary: &Dictionary<N>,
+    samples: Option<usize>,
+    priors: &[Prior],
+    options: BenchmarkOptions,
+    json: Option<PathBuf>,
+) -> Result<()> {
+    let answers = sample_answers(dictionary, samples);
+    let progress = progress_bar("Benchmark");
+    let comparison = benchmark::compare_priors(dictionary, &answers, priors, &options, &progress)?;
+
+    println!("strategy: {}", options.strategy);
+    println!("prior, mean turns, max turns, failures");
+    for (prior, report) in &comparison {
+        let stats = &report.stats;
+        println!(
+            "{prior}, {:.3}, {}, {}",
+            stats.mean_turns, stats.max_turns, stats.failures
+        );
+    }
+
+    if let Some(json) = json {
+        serde_json::to_writer_pretty(File::create(&json)?, &comparison)?;
+        println!("Comparison saved to {}", json.display());
+    }
+
+    Ok(())
+}
+
+pub fn multi<const N: usize>(
+    dictionary: &Dictionary<N>,
+    steps: &[String],
+    boards: usize,
+    top: usize,
+) -> Result<()> {
+    let mut state = MultiBoard::new(dictionary, boards);
+    for step in steps {
+        let (word, hints) = step.split_once(':').ok_or_else(|| {
+            format!("Expected guess in the word:hints,hints,... format, found \"{step}\"")
+        })?;
+        let word = parse_word::<N>(word)?;
+        let guess = dictionary
+            .words
+            .iter()
+            .position(|w| *w == word)
+            .ok_or_else(|| format!("{word} is not in the word list"))?;
+        let hints = hints
+            .split(',')
+            .map(|hints| {
+                HintsN::from_str(hints)
+                    .map_err(|err| format!("Invalid hints \"{hints}\" for {word}: {err}").into())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        state.add_guess(dictionary, guess, &hints)?;
+    }
+
+    for (i, board) in state.boards.iter().enumerate() {
+        if board.solved {
+            println!("board {}: solved", i + 1);
+        } else if let &[answer] = &board.answers[..] {
+            println!(
+                "board {}: the answer is {}",
+                i + 1,
+                dictionary.words[answer]
+            );
+        } else {
+            println!("board {}: {} possible answers", i + 1, board.answers.len());
+        }
+    }
+    if state.is_solved() {
+        return Ok(());
+    }
+
+    let progress = progress_bar("Entropies");
+    let scores = score_guesses(dictionary, &state, &MultiBoardOptions::default(), &progress)?;
+    println!("guess, total entropy, boards solved, score");
+    for score in scores.iter().take(top) {
+        println!(
+            "{}, {:.3}, {:.3}, {:.3}",
+            dictionary.words[score.guess], score.entropy, score.solve_probability, score.score
+        );
+    }
+
+    Ok(())
+}
+
+pub fn calibrate<const N: usize>(
+    dictionary: &Dictionary<N>,
+    samples: usize,
+    bins_per_bit: usize,
+    family: ModelFamily,
+    output: PathBuf,
+    plot: Option<PathBuf>,
+) -> Result<()> {
+    let answers = sample_answers(dictionary, Some(samples));
+    let unc_data = uncertainty_data(dictionary, &answers, &progress_bar("Simulations"))?
+        .into_iter()
+        .map(|(x, y)| (x.max(0.), y as f64))
+        .collect::<Vec<_>>();
+
+    let weighted = unc_data
+        .iter()
+        .map(|&(x, y)| (x, y, 1.))
+        .collect::<Vec<_>>();
+    let bins = bin_data(&weighted, bins_per_bit);
+    let (comparisons, failures) = compare_models(&bins);
+    // saved with any of the chosen models, the default one stands in
+    // when the log model couldn't be fitted but another one was chosen
+    let calibration = comparisons
+        .iter()
+        .find_map(|comparison| match comparison.model {
+            Model::Log(calibration) => Some(calibration),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let chosen = comparisons
+        .iter()
+        .find(|comparison| comparison.model.family() == family)
+        .ok_or_else(|| format!("Unable to fit the {family} model"))?;
+
+    println!("uncertainty, turns left, residual");
+    for (&((x0, x1), y), residual) in bins.iter().zip(&chosen.stats.residuals) {
+        println!("{x0:.2}-{x1:.2}, {y:.3}, {residual:+.3}");
+    }
+
+    println!("model, parameters, R², RMSE, AICc");
+    for ModelComparison { model, stats, aic } in &comparisons {
+        println!(
+            "{}, {}, {:.4}, {:.4}, {aic:.2}",
+            model.family(),
+            model.parameters(),
+            stats.r_squared,
+            stats.rmse
+        );
+    }
+    for (family, err) in failures {
+        println!("{family}: {err}");
+    }
+
+    let file = CalibrationFile {
+        samples: Some(samples),
+        bins_per_bit: Some(bins_per_bit),
+        model: (family != ModelFamily::Log).then(|| chosen.model.clone()),
+        fit: Some(chosen.stats.clone()),
+        ..calibration.into()
+    };
+    serde_json::to_writer_pretty(File::create(&output)?, &file)?;
+    println!(
+        "Calibration saved to {}: {}",
+        output.display(),
+        chosen.model
+    );
+
+    if let Some(plot) = plot {
+        let x_max = (dictionary.answers.len() as f64).log2() + 1.;
+        plot::plot_calibration(&plot, &unc_data, &chosen.model, x_max)?;
+        println!("Plot saved to {}", plot.display());
+    }
+
+    Ok(())
+}
+
+const PROGRESS_BAR_WIDTH: usize = 40;
+
+/// Progress drawing a bar on stderr, redrawn whenever another percent is done
+fn progress_bar(label: &'static str) -> Progress {
+    // the steps finish on several threads, the last percent drawn is locked
+    // so that the bar is only ever redrawn forward, one thread at a time
+    let drawn = Mutex::new(0);
+    Progress::with_callback(move |done, total| {
+        let percent = done * 100 / total;
+        let mut drawn = drawn.lock().unwrap();
+        // the first step starts the bar over, the progress restarts for every stage
+        if done == 1 {
+            *drawn = 0;
+        }
+        if done > 1 && percent <= *drawn {
+            return;
+        }
+        *drawn = percent;
+
+        let filled = done * PROGRESS_BAR_WIDTH / total;
+        eprint!(
+            "\r{label} [{}{}] {percent:>3}%",
+            "#".repeat(filled),
+            " ".repeat(PROGRESS_BAR_WIDTH - filled)
+        );
+        if done == total {
+            eprintln!();
+        }
+    })
+}
+
+pub fn entropy<const N: usize>(
+    dictionary: &Dictionary<N>,
+    top: usize,
+    sort: ScoreKind,
+    output: Option<PathBuf>,
+    calibration: Model,
+) -> Result<()> {
+    let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+    let progress = progress_bar("Entropies");
+    let entropies =
+        guess_summaries_with_progress(dictionary, None, &guesses, &dictionary.answers, &progress)
+            .ok_or("Entropy calculation was cancelled")?;
+    let mut scores = entropies_scored(
+        dictionary,
+        &dictionary.answers,
+        entropies,
+        None,
+        Some(&calibration),
+    );
+    rank_scores(&mut scores, sort);
+
+    println!("{SCORES_HEADER}");
+    for (i, entropies_data, turns) in scores.iter().take(top) {
+        println!(
+            "{}",
+            format_scores(&dictionary.words[*i], entropies_data, *turns)
+        );
+    }
+
+    if let Some(output) = output {
+        let mut file = File::create(&output)?;
+        writeln!(
+            file,
+            "word,entropy,expected_turns,max_bucket,expected_size,buckets,solve_probability"
+        )?;
+        for (i, entropies_data, turns) in &scores {
+            writeln!(
+                file,
+                "{},{},{turns},{},{},{},{}",
+                dictionary.words[*i],
+                entropies_data.entropy,
+                entropies_data.max_bucket,
+                entropies_data.expected_size,
+                entropies_data.buckets,
+                entropies_data.solve_probability
+            )?;
+        }
+        println!("Entropies saved to {}", output.display());
+    }
+
+    Ok(())
+}
+
+pub fn play<const N: usize>(
+    dictionary: &Dictionary<N>,
+    max_turns: usize,
+    hard_mode: bool,
+) -> Result<()> {
+    let answer = dictionary
+        .answers
+        .iter()
+        .choose(&mut rand::thread_rng())
+        .ok_or("The word list has no answers")?;
+    let correct = &dictionary.words[*answer];
+    let mut knowledge = KnowledgeN::none();
+    let mut turn = 0;
+
+    println!("Guess the {N}-letter word in {max_turns} turns");
+    let mut lines = io::stdin().lock().lines();
+    while turn < max_turns {
+        print!("{}> ", turn + 1);
+        io::stdout().flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        let guess = match parse_word::<N>(&line) {
+            Ok(guess) => guess,
+            Err(err) => {
+                println!("{err}");
+                continue;
+            }
+        };
+        if !dictionary.words.contains(&guess) {
+            println!("{guess} is not in the word list");
+            continue;
+        }
+        if hard_mode && !check(&guess, &knowledge) {
+            println!("{guess} ignores some of the revealed hints, not allowed in hard mode");
+            continue;
+        }
+
+        let (hints, knowledge_new) = get_hints_and_update(&guess, correct, knowledge);
+        knowledge = knowledge_new;
+        turn += 1;
+
+        println!("   {guess} {hints}");
+        if hints == HintsN::correct() {
+            println!("Solved in {turn} turns!");
+            return Ok(());
+        }
+    }
+
+    println!("The answer was {correct}");
+    Ok(())
+}
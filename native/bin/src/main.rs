@@ -1,140 +1,243 @@
-use std::env;
-
-use nalgebra::{DVector, Scalar};
-use num::One;
-use num_traits::Float;
-use plotters::prelude::*;
-use varpro::prelude::*;
-use varpro::solvers::levmar::{LevMarProblemBuilder, LevMarSolver};
-use we_core::algo;
-use we_core::data;
-use we_core::solvers::solve_random;
-use we_core::structs::{knowledge::KnowledgeN, word::WordN};
+mod assistant;
+mod commands;
+mod plot;
+
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use we_core::benchmark::BenchmarkOptions;
+use we_core::calibration::{CalibrationFile, Model, ModelFamily};
+use we_core::data::{self, LoadOptions};
+use we_core::dispatch_dictionary;
+use we_core::entropy::ScoreKind;
+use we_core::priors::Prior;
+use we_core::strategy::StrategyKind;
+use we_core::structs::Dictionary;
 use wordle_entropy_core as we_core;
 
-const WORDS_LENGTH: usize = 5;
-
-type Word = WordN<char, WORDS_LENGTH>;
-type Knowledge = KnowledgeN<WORDS_LENGTH>;
-
-// c * (x+1)^r log((x+1))
-pub fn log_f_s<S: Scalar + Float>(x: S, r: S, a: S, b: S) -> S {
-    let x = x + One::one();
-    b + a * x.powf(r) * x.ln()
-}
-
-pub fn log_f<S: Scalar + Float>(x: &DVector<S>, r: S) -> DVector<S> {
-    x.map(|x| x + One::one()).map(|x| x.powf(r) * x.ln())
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[clap(
+    author,
+    version,
+    about = "Wordle solver based on the expected information of guesses"
+)]
+struct Cli {
+    /// Word list with one `word[,probability[,answer]]` per line, separated by commas,
+    /// tabs or whitespace. Blank lines, `#` comments and a header are skipped.
+    #[clap(short, long)]
+    words: PathBuf,
+
+    /// Separate list of the possible answers, by default every word can be the answer
+    #[clap(short, long)]
+    answers: Option<PathBuf>,
+
+    /// Turns the second column, raw frequency counts or probabilities, into the probabilities
+    /// of the answers: uniform, raw, log, sigmoid[:midpoint:width] or rank
+    #[clap(short, long, default_value_t)]
+    prior: Prior,
+
+    /// Calibration saved by the `calibrate` command, the default one is used if not given
+    #[clap(short, long)]
+    calibration: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Command,
 }
 
-pub fn log_f_dr<S: Scalar + Float>(rvec: &DVector<S>, r: S) -> DVector<S> {
-    rvec.map(|x| x + One::one())
-        .map(|x| r * x.powf(r - One::one()) * x.ln())
+#[derive(Subcommand)]
+pub enum Command {
+    /// Ranks the next guesses given the guesses made so far
+    Suggest {
+        /// Guesses along with their hints, e.g. `crane:wocww`
+        /// (c - correct, o - out of place, w - wrong)
+        steps: Vec<String>,
+
+        /// Number of the best guesses printed
+        #[clap(short = 'n', long, default_value_t = 10)]
+        top: usize,
+
+        /// Score the guesses are ranked by: entropy, turns, max-bucket, expected-size,
+        /// buckets or solve-probability
+        #[clap(short, long, default_value_t)]
+        sort: ScoreKind,
+
+        #[clap(long)]
+        hard_mode: bool,
+    },
+    /// Solves the answers and prints statistics of the number of turns
+    Simulate {
+        /// Number of random answers solved, all of them if not given
+        #[clap(short = 'n', long)]
+        samples: Option<usize>,
+
+        /// Strategy choosing the guesses: entropy, turns, minimax, probable, random, worst-case
+        /// or lookahead
+        #[clap(short, long, default_value_t)]
+        strategy: StrategyKind,
+
+        /// Opening guess benchmarked separately, can be repeated
+        #[clap(long = "opener")]
+        openers: Vec<String>,
+
+        /// Prior applied on top of the loaded probabilities, benchmarked separately,
+        /// can be repeated
+        #[clap(long = "compare-prior")]
+        priors: Vec<Prior>,
+
+        #[clap(long)]
+        hard_mode: bool,
+
+        /// Writes the turns needed for each answer as CSV
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// Writes the full report as JSON
+        #[clap(long)]
+        json: Option<PathBuf>,
+    },
+    /// Fits the expected turns model to the solutions of random answers
+    Calibrate {
+        /// Number of random answers solved
+        #[clap(short = 'n', long, default_value_t = 200)]
+        samples: usize,
+
+        /// Bins per bit of uncertainty, the mean turns left of each bin are fitted
+        #[clap(short, long, default_value_t = 4)]
+        bins_per_bit: usize,
+
+        /// Model saved along with the log calibration: log, power-log, piecewise-linear
+        /// or isotonic. All of them are fitted and compared.
+        #[clap(short, long, default_value_t)]
+        model: ModelFamily,
+
+        /// Where the fitted calibration is saved as JSON, along with the goodness of fit
+        #[clap(short, long, default_value = "calibration.json")]
+        output: PathBuf,
+
+        /// Plots the data points along with the fitted curve
+        #[clap(short, long)]
+        plot: Option<PathBuf>,
+    },
+    /// Prints the entropies and scores of the opening words
+    Entropy {
+        /// Number of the best words printed
+        #[clap(short = 'n', long, default_value_t = 20)]
+        top: usize,
+
+        /// Score the words are ranked by: entropy, turns, max-bucket, expected-size,
+        /// buckets or solve-probability
+        #[clap(short, long, default_value_t)]
+        sort: ScoreKind,
+
+        /// Writes the entropies and scores of all the words as CSV
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Interactive assistant suggesting the guesses for a puzzle played elsewhere
+    Assistant {
+        /// Number of the suggested guesses printed
+        #[clap(short = 'n', long, default_value_t = 5)]
+        top: usize,
+
+        #[clap(long)]
+        hard_mode: bool,
+    },
+    /// Ranks the next guesses for several boards solved at once, as in Quordle
+    Multi {
+        /// Guesses along with the hints of every board, e.g. `crane:wocww,ccwww,wwwww,wwwow`
+        steps: Vec<String>,
+
+        /// Number of the boards
+        #[clap(short, long, default_value_t = 4)]
+        boards: usize,
+
+        /// Number of the best guesses printed
+        #[clap(short = 'n', long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Plays an interactive game against a random answer
+    Play {
+        #[clap(long, default_value_t = 6)]
+        max_turns: usize,
+
+        #[clap(long)]
+        hard_mode: bool,
+    },
 }
 
-fn fit(data: Vec<(f64, f64)>) -> (f64, f64, f64) {
-    let (x, y): (Vec<_>, Vec<_>) = data.into_iter().unzip();
-
-    let model = SeparableModelBuilder::<f64>::new(&["r"])
-        .function(&["r"], log_f)
-        .partial_deriv("r", log_f_dr)
-        .invariant_function(|x| x.clone())
-        .build()
-        .unwrap();
-
-    let problem = LevMarProblemBuilder::new()
-        .model(&model)
-        .x(x)
-        .y(y)
-        .initial_guess(&[0.])
-        .build()
-        .unwrap();
-
-    let (solved_problem, report) = LevMarSolver::new().minimize(problem);
-    assert!(report.termination.was_successful());
-    let alpha = solved_problem.params();
-    let c = solved_problem.linear_coefficients().unwrap();
-
-    (c[0], c[1], alpha[0])
-}
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let options = LoadOptions {
+        prior: cli.prior,
+        ..Default::default()
+    };
+    let (dictionary, warnings) =
+        data::load_words_any_with(&cli.words, cli.answers.as_ref(), &options)?;
+    for warning in warnings {
+        eprintln!("Warning: {warning}");
+    }
+    let calibration = match &cli.calibration {
+        Some(path) => serde_json::from_reader::<_, CalibrationFile>(File::open(path)?)?.model(),
+        None => Model::default(),
+    };
 
-pub fn print_example() {
-    let guess: Word = Word::try_from("śląsk").unwrap();
-    let correct: Word = Word::try_from("oślik").unwrap();
-    let knowledge = Knowledge::none();
-    let hints = algo::get_hints(&guess, &correct);
-    let knowledge = algo::update_knowledge(&guess, &hints, knowledge);
-
-    println!("{hints}");
-    println!("{knowledge:#?}");
-
-    let guess: Word = Word::try_from("rolka").unwrap();
-    let hints = algo::get_hints(&guess, &correct);
-    let knowledge = algo::update_knowledge(&guess, &hints, knowledge);
-    println!("{hints}");
-    println!("{knowledge:#?}");
+    dispatch_dictionary!(dictionary, dictionary => run(&dictionary, cli.command, calibration))
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() <= 1 {
-        eprintln!("Missing argument: path to word list");
-        std::process::exit(1);
+fn run<const N: usize>(
+    dictionary: &Dictionary<N>,
+    command: Command,
+    calibration: Model,
+) -> Result<()> {
+    match command {
+        Command::Suggest {
+            steps,
+            top,
+            sort,
+            hard_mode,
+        } => commands::suggest(dictionary, &steps, top, sort, hard_mode, calibration),
+        Command::Simulate {
+            samples,
+            strategy,
+            openers,
+            priors,
+            hard_mode,
+            output,
+            json,
+        } => {
+            let options = BenchmarkOptions {
+                strategy,
+                calibration,
+                hard_mode,
+                ..Default::default()
+            };
+            if priors.is_empty() {
+                commands::simulate(dictionary, samples, &openers, options, output, json)
+            } else {
+                commands::compare_priors(dictionary, samples, &priors, options, json)
+            }
+        }
+        Command::Calibrate {
+            samples,
+            bins_per_bit,
+            model,
+            output,
+            plot,
+        } => commands::calibrate(dictionary, samples, bins_per_bit, model, output, plot),
+        Command::Entropy { top, sort, output } => {
+            commands::entropy(dictionary, top, sort, output, calibration)
+        }
+        Command::Assistant { top, hard_mode } => {
+            assistant::assistant(dictionary, hard_mode, calibration, top)
+        }
+        Command::Multi { steps, boards, top } => commands::multi(dictionary, &steps, boards, top),
+        Command::Play {
+            max_turns,
+            hard_mode,
+        } => commands::play(dictionary, max_turns, hard_mode),
     }
-
-    let words_path = &args[1];
-
-    let dictionary = data::load_words::<_, WORDS_LENGTH>(words_path).unwrap();
-    let words = &dictionary.words;
-
-    let unc_data = solve_random(&dictionary, 200)
-        .into_iter()
-        .map(|(x, y)| (num::clamp(x, 0., f64::MAX), y as f64))
-        .collect::<Vec<_>>();
-    println!("uncertaintes points: {unc_data:?}");
-
-    let (a, b, r) = fit(unc_data.clone());
-    println!("{r}, {a}, {b}");
-
-    let y_max = 1.
-        + unc_data
-            .iter()
-            .map(|&(_, left)| left)
-            .max_by(|x, y| x.partial_cmp(y).unwrap())
-            .unwrap_or(7.) as f64;
-    let x_max = (words.len() as f64).log2() + 1.;
-
-    let root = BitMapBackend::new("/tmp/0.png", (1000, 700)).into_drawing_area();
-    root.fill(&WHITE)?;
-    let root = root.margin(10, 10, 10, 10);
-    let mut chart = ChartBuilder::on(&root)
-        .caption("y=x^2", ("sans-serif", 50).into_font())
-        .margin(5u32)
-        .x_label_area_size(30u32)
-        .y_label_area_size(30u32)
-        .build_cartesian_2d(0f64..x_max, 0f64..y_max)?;
-
-    chart.configure_mesh().draw()?;
-
-    let c = 5.;
-    chart.draw_series(LineSeries::new(
-        (0..=((c * x_max.floor()) as i32))
-            .map(|x| (x as f64) / c)
-            .map(|x| (x, log_f_s(x, r, a, b))),
-        &RED,
-    ))?;
-    //chart.draw_series(LineSeries::new((0..=(x_max.floor() as i32)).map(|x| (x as f64, (x*x) as f64)), &RED))?;
-
-    chart.draw_series(PointSeries::of_element(
-        unc_data,
-        2,
-        &BLACK,
-        &|c, s: i32, st| {
-            return Circle::new(c, s, st.filled());
-        },
-    ))?;
-
-    println!("ok");
-
-    Ok(())
 }
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use plotters::prelude::*;
+use we_core::calibration::{bounded_turns, Model};
+use wordle_entropy_core as we_core;
+
+use crate::Result;
+
+/// Plots the (uncertainty, turns left) points along with the fitted model
+pub fn plot_calibration(
+    path: &Path,
+    unc_data: &[(f64, f64)],
+    model: &Model,
+    x_max: f64,
+) -> Result<()> {
+    let y_max = 1. + unc_data.iter().map(|&(_, left)| left).fold(6., f64::max);
+
+    let root = BitMapBackend::new(path, (1000, 700)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root = root.margin(10, 10, 10, 10);
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Turns left by uncertainty", ("sans-serif", 50).into_font())
+        .margin(5u32)
+        .x_label_area_size(30u32)
+        .y_label_area_size(30u32)
+        .build_cartesian_2d(0f64..x_max, 0f64..y_max)?;
+
+    chart.configure_mesh().draw()?;
+
+    let c = 5.;
+    chart.draw_series(LineSeries::new(
+        (0..=((c * x_max.floor()) as i32))
+            .map(|x| (x as f64) / c)
+            .map(|x| (x, bounded_turns(x, model))),
+        &RED,
+    ))?;
+
+    chart.draw_series(PointSeries::of_element(
+        unc_data.iter().copied(),
+        2,
+        &BLACK,
+        &|c, s: i32, st| Circle::new(c, s, st.filled()),
+    ))?;
+
+    root.present()?;
+    Ok(())
+}
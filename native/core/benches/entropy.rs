@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use wordle_entropy_core::data::load_words;
+use wordle_entropy_core::entropy::{
+    calculate_entropies, calculate_entropies_with_matrix, guess_summaries,
+};
+use wordle_entropy_core::priors::Prior;
+use wordle_entropy_core::structs::{Dictionary, HintMatrix};
+
+const WORDS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../web/static/data/english_words_with_probs.csv"
+);
+
+fn entropy_benchmark(c: &mut Criterion) {
+    let dictionary: Dictionary<5> = load_words(WORDS).unwrap();
+    // a slice of the guesses against all the answers, the full run takes too long to repeat
+    let guesses = (0..dictionary.words.len()).step_by(20).collect::<Vec<_>>();
+    let matrix = HintMatrix::new(&dictionary);
+
+    let mut group = c.benchmark_group("calculate_entropies");
+    group.sample_size(10);
+    for prior in [Prior::Uniform, Prior::Raw] {
+        let dictionary = prior.apply_to(&dictionary);
+        group.bench_function(prior.name(), |b| {
+            b.iter(|| calculate_entropies(&dictionary, black_box(&guesses), &dictionary.answers))
+        });
+        group.bench_function(format!("{} summaries", prior.name()), |b| {
+            b.iter(|| guess_summaries(&dictionary, None, black_box(&guesses), &dictionary.answers))
+        });
+        group.bench_function(format!("{} with matrix", prior.name()), |b| {
+            b.iter(|| {
+                calculate_entropies_with_matrix(
+                    &dictionary,
+                    &matrix,
+                    black_box(&guesses),
+                    &dictionary.answers,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, entropy_benchmark);
+criterion_main!(benches);
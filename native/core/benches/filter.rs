@@ -0,0 +1,53 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use wordle_entropy_core::algo::{self, get_hints_and_update};
+use wordle_entropy_core::data::load_words;
+use wordle_entropy_core::filter::{self, KnowledgeFilter};
+use wordle_entropy_core::structs::{knowledge::KnowledgeN, Dictionary, WordN};
+
+const WORDS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../web/static/data/english_words_with_probs.csv"
+);
+
+fn knowledge_after(guesses: &[&str], correct: &str) -> KnowledgeN<5> {
+    let correct = WordN::try_from(correct).unwrap();
+    guesses.iter().fold(KnowledgeN::none(), |knowledge, guess| {
+        let guess = WordN::try_from(*guess).unwrap();
+        get_hints_and_update(&guess, &correct, knowledge).1
+    })
+}
+
+fn filter_benchmark(c: &mut Criterion) {
+    let dictionary: Dictionary<5> = load_words(WORDS).unwrap();
+    let answers = (0..dictionary.words.len()).collect::<Vec<_>>();
+
+    for (name, guesses) in [
+        ("one guess", &["crane"][..]),
+        ("two guesses", &["crane", "lousy"][..]),
+    ] {
+        let knowledge = knowledge_after(guesses, "sweet");
+        let mut group = c.benchmark_group(name);
+
+        group.bench_function("check", |b| {
+            b.iter(|| algo::get_answers(&dictionary.words, black_box(&answers), &knowledge))
+        });
+        group.bench_function("filter", |b| {
+            b.iter(|| filter::get_answers(&dictionary, black_box(&answers), &knowledge))
+        });
+
+        let knowledge_filter = KnowledgeFilter::new(&dictionary.translator, &knowledge);
+        let mut retained = Vec::with_capacity(answers.len());
+        group.bench_function("retain", |b| {
+            b.iter(|| {
+                retained.clear();
+                retained.extend_from_slice(&answers);
+                knowledge_filter.retain(&dictionary.words_bytes, black_box(&mut retained));
+            })
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, filter_benchmark);
+criterion_main!(benches);
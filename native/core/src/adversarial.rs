@@ -0,0 +1,378 @@
+use std::cmp::Reverse;
+
+use fxhash::FxHashMap;
+
+use crate::{
+    algo::{filter_answers, get_hints, update_knowledge},
+    entropy::guess_summaries,
+    filter::get_guesses,
+    progress::{Cancelled, Progress},
+    strategy::{Minimax, Strategy, Turn},
+    structs::{hints::HintsN, knowledge::KnowledgeN, word::WordN, Dictionary, HintMatrix},
+};
+
+/// Index of the hints the adversary gives for the `guess`, the ones leaving the most answers.
+/// On a tie it avoids the correct hints.
+pub fn adversary_hints<const N: usize>(
+    dictionary: &Dictionary<N>,
+    guess: usize,
+    answers: &[usize],
+) -> usize {
+    let words_bytes = &dictionary.words_bytes;
+    largest_bucket::<N, _>(answers, |answer| {
+        get_hints(&words_bytes[guess], &words_bytes[answer]).to_ind()
+    })
+}
+
+/// Same as [`adversary_hints`], with the hints looked up in the precomputed `matrix`
+pub fn adversary_hints_with_matrix<const N: usize>(
+    matrix: &HintMatrix,
+    guess: usize,
+    answers: &[usize],
+) -> usize {
+    largest_bucket::<N, _>(answers, |answer| matrix.get(guess, answer))
+}
+
+fn largest_bucket<const N: usize, F>(answers: &[usize], hints_ind: F) -> usize
+where
+    F: Fn(usize) -> usize,
+{
+    let mut counts = FxHashMap::<usize, usize>::default();
+    for &answer in answers {
+        *counts.entry(hints_ind(answer)).or_default() += 1;
+    }
+
+    let correct_ind = HintsN::<N>::correct().to_ind();
+    counts
+        .into_iter()
+        .max_by_key(|&(hints, count)| (count, hints != correct_ind, Reverse(hints)))
+        .expect("No answers left")
+        .0
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AdversarialOptions {
+    /// Number of the guesses with the smallest worst case bucket tried at every step
+    pub candidates: usize,
+    /// Longest game searched for
+    pub max_turns: usize,
+}
+
+impl Default for AdversarialOptions {
+    fn default() -> Self {
+        Self {
+            candidates: 5,
+            max_turns: 8,
+        }
+    }
+}
+
+/// Largest number of the positions remembered by the search, it starts over once it's full
+const MEMO_MAX_ENTRIES: usize = 100_000;
+
+struct Search<'a, const N: usize> {
+    dictionary: &'a Dictionary<N>,
+    matrix: Option<&'a HintMatrix>,
+    hard_mode: bool,
+    options: AdversarialOptions,
+    progress: &'a Progress,
+    /// The best guess with its number of turns, or the number of turns it can't be won within,
+    /// keyed by the hash of the answers left and, in hard mode, of the guesses allowed.
+    /// Two positions with the same 64-bit hash are unlikely enough to be ignored.
+    memo: FxHashMap<u64, Result<(usize, usize), usize>>,
+}
+
+impl<'a, const N: usize> Search<'a, N> {
+    fn hints_of(&self, guess: usize, answer: usize) -> usize {
+        match self.matrix {
+            Some(matrix) => matrix.get(guess, answer),
+            None => {
+                let words_bytes = &self.dictionary.words_bytes;
+                get_hints(&words_bytes[guess], &words_bytes[answer]).to_ind()
+            }
+        }
+    }
+
+    /// The guess winning in the fewest turns against the adversary with the `answers` left,
+    /// as long as it needs at most `turns_left` turns
+    fn search(
+        &mut self,
+        knowledge: &KnowledgeN<N>,
+        answers: &[usize],
+        turns_left: usize,
+    ) -> Result<Option<(usize, usize)>, Cancelled> {
+        self.progress.check()?;
+        match (answers, turns_left) {
+            (_, 0) | ([], _) => return Ok(None),
+            (&[answer], _) => return Ok(Some((answer, 1))),
+            (_, 1) => return Ok(None),
+            _ => (),
+        }
+
+        let guesses = get_guesses(self.dictionary, knowledge, self.hard_mode);
+        // in hard mode the same answers can be left with different guesses allowed
+        let key = if self.hard_mode {
+            fxhash::hash64(&(answers, &guesses))
+        } else {
+            fxhash::hash64(answers)
+        };
+        match self.memo.get(&key) {
+            Some(&Ok((guess, turns))) => return Ok((turns <= turns_left).then_some((guess, turns))),
+            Some(&Err(turns)) if turns >= turns_left => return Ok(None),
+            _ => (),
+        }
+
+        let best = self.search_guesses(knowledge, &guesses, answers, turns_left)?;
+        if self.memo.len() >= MEMO_MAX_ENTRIES {
+            self.memo.clear();
+        }
+        self.memo.insert(key, best.ok_or(turns_left));
+        Ok(best)
+    }
+
+    fn search_guesses(
+        &mut self,
+        knowledge: &KnowledgeN<N>,
+        guesses: &[usize],
+        answers: &[usize],
+        turns_left: usize,
+    ) -> Result<Option<(usize, usize)>, Cancelled> {
+        let dictionary = self.dictionary;
+        let entropies = guess_summaries(dictionary, self.matrix, guesses, answers);
+        let mut candidates = entropies
+            .into_iter()
+            // a guess which splits nothing off never brings the game closer to the end
+            .filter(|(_, data)| data.max_bucket < answers.len())
+            .map(|(guess, data)| (guess, data.max_bucket, !answers.contains(&guess)))
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|&(guess, max_bucket, not_answer)| (max_bucket, not_answer, guess));
+        candidates.truncate(self.options.candidates);
+
+        let is_root = turns_left == self.options.max_turns;
+        if is_root {
+            self.progress.start(candidates.len());
+        }
+
+        let mut best: Option<(usize, usize)> = None;
+        for (guess, _, _) in candidates {
+            let limit = best.map(|(_, turns)| turns - 1).unwrap_or(turns_left);
+            let hints = largest_bucket::<N, _>(answers, |answer| self.hints_of(guess, answer));
+            let bucket = match self.matrix {
+                Some(matrix) => filter_answers(matrix, answers, guess, hints),
+                None => answers
+                    .iter()
+                    .copied()
+                    .filter(|&answer| self.hints_of(guess, answer) == hints)
+                    .collect(),
+            };
+            let knowledge = update_knowledge(
+                &dictionary.words[guess],
+                &dictionary.hints[hints],
+                knowledge.clone(),
+            );
+
+            if let Some((_, turns)) = self.search(&knowledge, &bucket, limit - 1)? {
+                best = Some((guess, turns + 1));
+            }
+            if is_root {
+                self.progress.advance();
+            }
+            // no guess can win against the adversary right away
+            if best.map(|(_, turns)| turns) == Some(2) {
+                break;
+            }
+        }
+
+        Ok(best)
+    }
+}
+
+/// The guess winning against the adversary in the fewest turns in the worst case,
+/// along with that number of turns. Only the guesses with the smallest largest buckets
+/// are searched, so the result is not necessarily optimal. In hard mode every guess
+/// of the search keeps to the hints revealed before it. The `progress` counts the first
+/// guesses tried.
+pub fn best_worst_case_guess<const N: usize>(
+    turn: &Turn<N>,
+    options: &AdversarialOptions,
+    progress: &Progress,
+) -> Result<Option<(usize, usize)>, Cancelled> {
+    let mut search = Search {
+        dictionary: turn.dictionary,
+        matrix: turn.matrix,
+        hard_mode: turn.hard_mode,
+        options: *options,
+        progress,
+        memo: FxHashMap::default(),
+    };
+    search.search(&turn.knowledge(), turn.answers, options.max_turns)
+}
+
+/// Minimizes the number of turns needed against the adversary
+pub struct WorstCase(pub AdversarialOptions);
+
+impl<const N: usize> Strategy<N> for WorstCase {
+    fn choose(&self, turn: &Turn<N>) -> usize {
+        self.choose_with_progress(turn, &Progress::new())
+            .expect("Search can't be cancelled without the progress")
+    }
+
+    /// The `progress` counts the first guesses tried
+    fn choose_with_progress(
+        &self,
+        turn: &Turn<N>,
+        progress: &Progress,
+    ) -> Result<usize, Cancelled> {
+        Ok(best_worst_case_guess(turn, &self.0, progress)?
+            .map(|(guess, _)| guess)
+            .unwrap_or_else(|| Minimax.choose(turn)))
+    }
+}
+
+/// Plays against the adversary with the guesses chosen by `strategy`, for at most `max_turns`
+pub fn play_adversarial<const N: usize>(
+    strategy: &dyn Strategy<N>,
+    dictionary: &Dictionary<N>,
+    matrix: Option<&HintMatrix>,
+    hard_mode: bool,
+    max_turns: usize,
+) -> Vec<(WordN<char, N>, HintsN<N>)> {
+    let mut answers = dictionary.answers.clone();
+    let mut history = vec![];
+
+    while history.len() < max_turns {
+        let turn = Turn {
+            dictionary,
+            matrix,
+            answers: &answers,
+            history: &history,
+            hard_mode,
+            entropies: None,
+        };
+        let guess = strategy.choose(&turn);
+
+        let words_bytes = &dictionary.words_bytes;
+        let hints_of = |answer: usize| match matrix {
+            Some(matrix) => matrix.get(guess, answer),
+            None => get_hints(&words_bytes[guess], &words_bytes[answer]).to_ind(),
+        };
+        let hints = largest_bucket::<N, _>(&answers, hints_of);
+        answers.retain(|&answer| hints_of(answer) == hints);
+
+        history.push((
+            dictionary.words[guess].clone(),
+            dictionary.hints[hints].clone(),
+        ));
+        if hints == HintsN::<N>::correct().to_ind() {
+            break;
+        }
+    }
+
+    history
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::{check, get_hints_and_update};
+    use crate::entropy::calculate_entropies_with_matrix;
+    use crate::solvers::solve;
+    use crate::test_util::abc_dictionary_with_answers;
+    use std::str::FromStr;
+
+    #[test]
+    fn adversary_game() {
+        let dictionary = abc_dictionary_with_answers(vec![0, 1, 2, 3]);
+
+        // "abcd" only splits itself off
+        let hints = adversary_hints(&dictionary, 0, &dictionary.answers);
+        assert_ne!(HintsN::<4>::correct().to_ind(), hints);
+        let matrix = HintMatrix::new(&dictionary);
+        assert_eq!(
+            hints,
+            adversary_hints_with_matrix::<4>(&matrix, 0, &dictionary.answers)
+        );
+
+        let options = AdversarialOptions::default();
+        let progress = Progress::new();
+        let turn = Turn {
+            dictionary: &dictionary,
+            matrix: Some(&matrix),
+            answers: &dictionary.answers,
+            history: &[],
+            hard_mode: false,
+            entropies: None,
+        };
+        assert_eq!(
+            Ok(Some((4, 2))),
+            best_worst_case_guess(&turn, &options, &progress)
+        );
+        assert!(progress.done() > 0);
+        progress.cancel();
+        assert_eq!(
+            Err(Cancelled),
+            best_worst_case_guess(&turn, &options, &progress)
+        );
+        assert_eq!(
+            Err(Cancelled),
+            WorstCase(options).choose_with_progress(&turn, &progress)
+        );
+
+        let history = play_adversarial(&WorstCase(options), &dictionary, None, false, 6);
+        assert_eq!(2, history.len());
+        assert_eq!(
+            Some(&HintsN::correct()),
+            history.last().map(|(_, hints)| hints)
+        );
+    }
+
+    #[test]
+    fn hard_mode_keeps_to_hints() {
+        let dictionary = abc_dictionary_with_answers(vec![0, 1, 2, 3]);
+        let matrix = HintMatrix::new(&dictionary);
+        let options = AdversarialOptions::default();
+
+        // "abcd" is out, only the other "abc?" words keep to the hints
+        let history = [(
+            dictionary.words[0].clone(),
+            HintsN::from_str("cccw").unwrap(),
+        )];
+        let answers = [1, 2, 3];
+        for hard_mode in [false, true] {
+            let turn = Turn {
+                dictionary: &dictionary,
+                matrix: Some(&matrix),
+                answers: &answers,
+                history: &history,
+                hard_mode,
+                entropies: None,
+            };
+            let guess = WorstCase(options).choose(&turn);
+            assert_eq!(!hard_mode, guess == 4);
+            assert!(turn.guesses().contains(&guess));
+        }
+
+        let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+        let initial_entropies =
+            calculate_entropies_with_matrix(&dictionary, &matrix, &guesses, &dictionary.answers);
+        for &answer in &dictionary.answers {
+            let correct = &dictionary.words[answer];
+            let (guesses, _, _, _) = solve(
+                &WorstCase(options),
+                &initial_entropies,
+                &dictionary,
+                &matrix,
+                correct,
+                true,
+                false,
+            );
+            assert_eq!(Some(correct), guesses.last());
+
+            let mut knowledge = KnowledgeN::none();
+            for guess in &guesses {
+                assert!(check(guess, &knowledge), "{guess} ignores the hints");
+                knowledge = get_hints_and_update(guess, correct, knowledge).1;
+            }
+        }
+    }
+}
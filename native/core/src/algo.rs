@@ -4,6 +4,7 @@ use crate::structs::{
     knowledge::KnowledgeN,
     knowledge::PartialChar,
     word::WordN,
+    HintMatrix,
 };
 use fxhash::FxHashMap;
 use itertools::izip;
@@ -35,6 +36,51 @@ where
     hints
 }
 
+/// Index of the hints [`get_hints`] returns for a single guess against many answers,
+/// the same as [`HintsN::to_ind`], computed with bit masks without building the hints.
+/// The positions of each of the guess letters are looked up instead of compared.
+pub struct GuessHints<const N: usize> {
+    guess: WordN<u8, N>,
+    /// Bit mask of the positions of every letter in the guess
+    positions: [u8; 256],
+}
+
+impl<const N: usize> GuessHints<N> {
+    pub fn new(guess: &WordN<u8, N>) -> Self {
+        let mut positions = [0; 256];
+        for (i, &letter) in guess.0.iter().enumerate() {
+            positions[letter as usize] |= 1 << i;
+        }
+
+        Self {
+            guess: guess.clone(),
+            positions,
+        }
+    }
+
+    pub fn ind(&self, correct: &WordN<u8, N>) -> usize {
+        let mut placed = 0u32;
+        for i in 0..N {
+            placed |= ((self.guess.0[i] == correct.0[i]) as u32) << i;
+        }
+
+        // guess letters already marked, either placed or out of place,
+        // each of the other letters of the answer marks the first unmarked same letter of the guess
+        let mut used = placed;
+        for i in 0..N {
+            let same = self.positions[correct.0[i] as usize] as u32;
+            let free = same & !used & (placed >> i & 1).wrapping_sub(1);
+            used |= free & free.wrapping_neg();
+        }
+
+        let mut ind = 0;
+        for i in 0..N {
+            ind = 3 * ind + (placed >> i & 1) as usize + (used >> i & 1) as usize;
+        }
+        ind
+    }
+}
+
 pub fn update_knowledge<const N: usize>(
     guess: &WordN<char, N>,
     hints: &HintsN<N>,
@@ -53,10 +99,19 @@ pub fn update_knowledge<const N: usize>(
         known_now
     };
 
+    // a gray letter means the answer has no more of it than marked in the same guess
+    let max_counts_now = guess
+        .0
+        .into_iter()
+        .zip(hints.0)
+        .filter(|&(_, h)| h == Hint::Wrong)
+        .map(|(c, _)| (c, known_now.get(&c).copied().unwrap_or_default()))
+        .collect::<FxHashMap<_, _>>();
+
     let ruled_out_now = guess
         .0
         .into_iter()
-        .zip(hints.0.into_iter())
+        .zip(hints.0)
         .filter(|&(c, h)| h == Hint::Wrong && !known_now.contains_key(&c))
         .map(|(c, _)| c)
         .collect::<Vec<_>>();
@@ -90,13 +145,21 @@ pub fn update_knowledge<const N: usize>(
         known
     };
 
-    let knowledge = KnowledgeN {
+    let max_counts = {
+        let mut max_counts = knowledge.max_counts;
+        for (char, count) in max_counts_now {
+            let old_count = max_counts.entry(char).or_insert(count);
+            *old_count = count.min(*old_count);
+        }
+        max_counts
+    };
+
+    KnowledgeN {
         known,
+        max_counts,
         ruled_out,
         placed,
-    };
-
-    knowledge
+    }
 }
 
 pub fn get_hints_and_update<const N: usize>(
@@ -117,6 +180,7 @@ pub fn get_valid_hints<const N: usize>(
 ) -> ValidHints {
     let ruled_out = &knowledge.ruled_out;
     let known = &knowledge.known;
+    let max_counts = &knowledge.max_counts;
 
     let vec = knowledge
         .placed
@@ -126,8 +190,9 @@ pub fn get_valid_hints<const N: usize>(
         .enumerate()
         .map(|(i, (p, g))| {
             let mut known = known.clone();
+            let mut max_left = max_counts.get(g).copied();
 
-            for (_, (&h, &g)) in hints
+            for (_, (&h, &other)) in hints
                 .0
                 .iter()
                 .zip(guess.0.iter())
@@ -135,41 +200,34 @@ pub fn get_valid_hints<const N: usize>(
                 .filter(|&(j, _)| j != i)
             {
                 if h != Hint::Wrong {
-                    if let Some(i) = known.get_mut(&g) {
-                        *i = i.saturating_sub(1);
+                    if let Some(count) = known.get_mut(&other) {
+                        *count = count.saturating_sub(1);
+                    }
+                    if other == *g {
+                        max_left = max_left.map(|count| count.saturating_sub(1));
                     }
                 }
             }
 
-            let char_known = if let Some(&n) = known.get(g) {
-                if n == 0 {
-                    false
-                } else {
-                    true
-                }
-            } else {
-                false
-            };
+            let char_known = matches!(known.get(g), Some(&n) if n > 0);
 
             match p {
                 PartialChar::Some(c) if g == c => {
                     vec![Hint::Correct]
                 }
-                _ if ruled_out.contains(&g) => {
+                _ if ruled_out.contains(g) || max_left == Some(0) => {
                     vec![Hint::Wrong]
                 }
-                PartialChar::Some(c) if g != c => {
+                PartialChar::Some(_) => {
                     vec![Hint::Wrong, Hint::OutOfPlace]
                 }
-                PartialChar::Excluded(excluded) if excluded.contains(&g) => {
+                PartialChar::Excluded(excluded) if excluded.contains(g) => {
                     vec![Hint::Wrong, Hint::OutOfPlace]
                 }
-                PartialChar::Some(_) | PartialChar::Excluded(_) | PartialChar::None
-                    if char_known =>
-                {
+                PartialChar::Excluded(_) | PartialChar::None if char_known => {
                     vec![Hint::OutOfPlace, Hint::Correct]
                 }
-                PartialChar::Some(_) | PartialChar::Excluded(_) | PartialChar::None => {
+                PartialChar::Excluded(_) | PartialChar::None => {
                     vec![Hint::Wrong, Hint::OutOfPlace, Hint::Correct]
                 }
             }
@@ -181,6 +239,7 @@ pub fn get_valid_hints<const N: usize>(
 
 pub fn check<const N: usize>(word: &WordN<char, N>, knowledge: &KnowledgeN<N>) -> bool {
     let mut known_left = knowledge.known.clone();
+    let mut max_left = knowledge.max_counts.clone();
 
     for (w, p) in izip!(word.0, &knowledge.placed.word) {
         if knowledge.ruled_out.contains(&w) && *p != PartialChar::Some(w) {
@@ -195,33 +254,88 @@ pub fn check<const N: usize>(word: &WordN<char, N>, knowledge: &KnowledgeN<N>) -
         if let Some(count) = known_left.get_mut(&w) {
             *count = count.saturating_sub(1);
         }
+        match max_left.get_mut(&w) {
+            Some(0) => return false,
+            Some(count) => *count -= 1,
+            None => (),
+        }
     }
 
-    known_left.retain(|_, &mut v| v > 0);
-
-    if known_left.len() > 0 {
-        return false;
-    }
-
-    return true;
+    known_left.values().all(|&v| v == 0)
 }
 
+/// Narrows down `answers` (indices into `words`) to the ones still consistent with `knowledge`
 pub fn get_answers<const N: usize>(
-    words: Vec<WordN<char, N>>,
+    words: &[WordN<char, N>],
+    answers: &[usize],
     knowledge: &KnowledgeN<N>,
 ) -> Vec<usize> {
-    words
-        .into_iter()
-        .enumerate()
-        .filter(|(_, word)| check(word, knowledge))
-        .map(|(i, _)| i)
+    answers
+        .iter()
+        .copied()
+        .filter(|&i| check(&words[i], knowledge))
         .collect()
 }
 
+/// Narrows down `answers` to the ones that would have given exactly the same hints
+/// for every guess in the `history`. Unlike [`get_answers`] it doesn't depend on
+/// what `KnowledgeN` is able to keep track of.
+pub fn get_consistent_answers<const N: usize>(
+    words: &[WordN<char, N>],
+    answers: &[usize],
+    history: &[(WordN<char, N>, HintsN<N>)],
+) -> Vec<usize> {
+    answers
+        .iter()
+        .copied()
+        .filter(|&i| {
+            history
+                .iter()
+                .all(|(guess, hints)| &get_hints(guess, &words[i]) == hints)
+        })
+        .collect()
+}
+
+/// Narrows down `answers` to the ones giving the same `hints` for the `guess`,
+/// comparing the precomputed hints instead of checking the gathered knowledge
+pub fn filter_answers(
+    matrix: &HintMatrix,
+    answers: &[usize],
+    guess: usize,
+    hints: usize,
+) -> Vec<usize> {
+    answers
+        .iter()
+        .copied()
+        .filter(|&i| matrix.get(guess, i) == hints)
+        .collect()
+}
+
+/// Splits the `answers` into buckets by the hints they give for the `guess`,
+/// returns the non-empty buckets along with the index of their hints, in the order of the hints
+pub fn partition_answers(
+    matrix: &HintMatrix,
+    guess: usize,
+    answers: &[usize],
+) -> Vec<(usize, Vec<usize>)> {
+    let mut buckets = FxHashMap::<usize, Vec<usize>>::default();
+    for &answer in answers {
+        buckets
+            .entry(matrix.get(guess, answer))
+            .or_default()
+            .push(answer);
+    }
+
+    let mut buckets = buckets.into_iter().collect::<Vec<_>>();
+    buckets.sort_unstable_by_key(|&(hints, _)| hints);
+    buckets
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::translator::Translator;
+    use proptest::prelude::*;
     use rstest::rstest;
     use std::str::FromStr;
 
@@ -246,4 +360,77 @@ mod tests {
         let hints = get_hints(&guess_b, &answer_b);
         assert_eq!(Hints::from_str(expected).unwrap(), hints);
     }
+
+    #[test]
+    fn consistent_answers_count_upper_bound() {
+        let words: Vec<Word> = ["crane", "evade", "theme"]
+            .into_iter()
+            .map(|w| w.try_into().unwrap())
+            .collect();
+        let guess: Word = "geese".try_into().unwrap();
+        let (hints, knowledge) = get_hints_and_update(&guess, &words[0], KnowledgeN::none());
+
+        // the gray "e"s of "geese" say the answer has a single "e"
+        assert_eq!(vec![0], get_answers(&words, &[0, 1, 2], &knowledge));
+        assert_eq!(
+            vec![0],
+            get_consistent_answers(&words, &[0, 1, 2], &[(guess, hints)])
+        );
+    }
+
+    #[test]
+    fn max_counts_valid_hints() {
+        let guess: Word = "geese".try_into().unwrap();
+        let correct: Word = "crane".try_into().unwrap();
+        let (_, knowledge) = get_hints_and_update(&guess, &correct, KnowledgeN::none());
+        assert_eq!(Some(&1), knowledge.max_counts.get(&'e'));
+
+        // with the single "e" already marked, the other "e" can only be gray
+        let next: Word = "elope".try_into().unwrap();
+        let hints = Hints::from_str("WWWWC").unwrap();
+        let valid_hints = get_valid_hints(&next, &hints, &knowledge);
+        assert_eq!(vec![Hint::Wrong], valid_hints.0[0]);
+    }
+
+    fn word_strategy() -> impl Strategy<Value = Word> {
+        // a small alphabet, so that repeated letters are common
+        "[abce]{5}".prop_map(|w| w.as_str().try_into().unwrap())
+    }
+
+    proptest! {
+        #[test]
+        fn hints_ind_matches_hints(guess in word_strategy(), correct in word_strategy()) {
+            let ind = get_hints(&guess, &correct).to_ind();
+            let translator = Translator::generate(&[guess.clone(), correct.clone()]);
+            let guess_hints = GuessHints::new(&translator.to_bytes(&guess));
+            prop_assert_eq!(ind, guess_hints.ind(&translator.to_bytes(&correct)));
+        }
+
+        /// Hint consistency never keeps an answer that the knowledge rules out,
+        /// and never drops the correct answer. The knowledge may keep more answers.
+        #[test]
+        fn consistent_answers_subset(
+            correct in word_strategy(),
+            guesses in prop::collection::vec(word_strategy(), 1..4),
+            words in prop::collection::vec(word_strategy(), 1..30),
+        ) {
+            let mut words = words;
+            words.push(correct.clone());
+            let answers = (0..words.len()).collect::<Vec<_>>();
+
+            let mut knowledge = KnowledgeN::none();
+            let mut history = vec![];
+            for guess in guesses {
+                let (hints, knowledge_new) = get_hints_and_update(&guess, &correct, knowledge);
+                knowledge = knowledge_new;
+                history.push((guess, hints));
+            }
+
+            let from_knowledge = get_answers(&words, &answers, &knowledge);
+            let consistent = get_consistent_answers(&words, &answers, &history);
+
+            prop_assert!(consistent.contains(&(words.len() - 1)));
+            prop_assert!(consistent.iter().all(|i| from_knowledge.contains(i)));
+        }
+    }
 }
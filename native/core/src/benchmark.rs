@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calibration::Model,
+    entropy::calculate_entropies_with_matrix_and_progress,
+    priors::Prior,
+    progress::{Cancelled, Progress},
+    solvers::solve,
+    strategy::{Strategy, StrategyKind, Turn},
+    structs::{Dictionary, HintMatrix},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkOptions {
+    pub strategy: StrategyKind,
+    pub calibration: Model,
+    /// Opening guesses benchmarked separately, the solver picks the opener if empty
+    pub openers: Vec<usize>,
+    pub hard_mode: bool,
+    /// Games taking more turns count as failures
+    pub max_turns: usize,
+    /// Number of the answers taking the most turns kept in the report
+    pub worst_count: usize,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self {
+            strategy: StrategyKind::default(),
+            calibration: Model::default(),
+            openers: vec![],
+            hard_mode: false,
+            max_turns: 6,
+            worst_count: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub opener: String,
+    pub answer: String,
+    pub turns: usize,
+    pub guesses: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TurnsStats {
+    pub games: usize,
+    pub mean_turns: f64,
+    pub max_turns: usize,
+    /// Number of the games solved in the given number of turns
+    pub histogram: BTreeMap<usize, usize>,
+    pub failures: usize,
+}
+
+impl TurnsStats {
+    fn new<'a>(results: impl Iterator<Item = &'a GameResult>, max_turns: usize) -> Self {
+        let mut histogram = BTreeMap::new();
+        for result in results {
+            *histogram.entry(result.turns).or_default() += 1;
+        }
+
+        let games = histogram.values().sum();
+        let total_turns: usize = histogram.iter().map(|(turns, count)| turns * count).sum();
+
+        Self {
+            games,
+            mean_turns: total_turns as f64 / games.max(1) as f64,
+            max_turns: histogram.keys().last().copied().unwrap_or_default(),
+            failures: histogram
+                .range(max_turns + 1..)
+                .map(|(_, count)| count)
+                .sum(),
+            histogram,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub strategy: StrategyKind,
+    pub stats: TurnsStats,
+    pub openers: BTreeMap<String, TurnsStats>,
+    /// Games taking the most turns, the worst first
+    pub worst: Vec<GameResult>,
+    pub results: Vec<GameResult>,
+}
+
+impl BenchmarkReport {
+    /// Results of every game, one per line
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("opener,answer,turns,guesses\n");
+        for result in &self.results {
+            writeln!(
+                csv,
+                "{},{},{},{}",
+                result.opener,
+                result.answer,
+                result.turns,
+                result.guesses.join(" ")
+            )
+            .unwrap();
+        }
+        csv
+    }
+}
+
+/// Plays the `opener` first and leaves the rest to `strategy`
+struct WithOpener<'a, const N: usize> {
+    opener: usize,
+    strategy: &'a dyn Strategy<N>,
+}
+
+impl<'a, const N: usize> Strategy<N> for WithOpener<'a, N> {
+    fn choose(&self, turn: &Turn<N>) -> usize {
+        if turn.history.is_empty() {
+            self.opener
+        } else {
+            self.strategy.choose(turn)
+        }
+    }
+}
+
+/// Solves every one of the `answers` (indices into `dictionary.words`) once for every opener.
+/// The `progress` counts the rows of the hint matrix, then the initial entropies
+/// and then the games played.
+pub fn benchmark<const N: usize>(
+    dictionary: &Dictionary<N>,
+    answers: &[usize],
+    options: &BenchmarkOptions,
+    progress: &Progress,
+) -> Result<BenchmarkReport, Cancelled> {
+    let matrix = HintMatrix::with_progress(dictionary, progress)?;
+    let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+    let initial_entropies = calculate_entropies_with_matrix_and_progress(
+        dictionary,
+        &matrix,
+        &guesses,
+        &dictionary.answers,
+        progress,
+    )
+    .ok_or(Cancelled)?;
+
+    let strategy = options.strategy.strategy(options.calibration.clone());
+    let strategies = if options.openers.is_empty() {
+        vec![strategy]
+    } else {
+        options
+            .openers
+            .iter()
+            .map(|&opener| {
+                Box::new(WithOpener {
+                    opener,
+                    strategy: strategy.as_ref(),
+                }) as Box<dyn Strategy<N>>
+            })
+            .collect()
+    };
+
+    progress.start(strategies.len() * answers.len());
+    let mut results = vec![];
+    for strategy in &strategies {
+        let play = |&answer: &usize| {
+            progress.check()?;
+            let (guesses, _, _, _) = solve(
+                strategy.as_ref(),
+                &initial_entropies,
+                dictionary,
+                &matrix,
+                &dictionary.words[answer],
+                options.hard_mode,
+                false,
+            );
+            let guesses = guesses.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+            progress.advance();
+
+            Ok(GameResult {
+                opener: guesses[0].clone(),
+                answer: dictionary.words[answer].to_string(),
+                turns: guesses.len(),
+                guesses,
+            })
+        };
+
+        #[cfg(feature = "parallel")]
+        results.extend(
+            answers
+                .par_iter()
+                .map(play)
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+
+        #[cfg(not(feature = "parallel"))]
+        results.extend(answers.iter().map(play).collect::<Result<Vec<_>, _>>()?);
+    }
+
+    let mut openers = BTreeMap::new();
+    for result in &results {
+        openers
+            .entry(result.opener.clone())
+            .or_insert_with(Vec::new)
+            .push(result);
+    }
+    let openers = openers
+        .into_iter()
+        .map(|(opener, results)| {
+            let stats = TurnsStats::new(results.into_iter(), options.max_turns);
+            (opener, stats)
+        })
+        .collect();
+
+    let mut worst = results.clone();
+    worst.sort_by_key(|result| std::cmp::Reverse(result.turns));
+    worst.truncate(options.worst_count);
+
+    Ok(BenchmarkReport {
+        strategy: options.strategy,
+        stats: TurnsStats::new(results.iter(), options.max_turns),
+        openers,
+        worst,
+        results,
+    })
+}
+
+/// Benchmarks the same answers with each of the `priors` applied to the dictionary probabilities,
+/// the `progress` starts over with each of them
+pub fn compare_priors<const N: usize>(
+    dictionary: &Dictionary<N>,
+    answers: &[usize],
+    priors: &[Prior],
+    options: &BenchmarkOptions,
+    progress: &Progress,
+) -> Result<Vec<(Prior, BenchmarkReport)>, Cancelled> {
+    priors
+        .iter()
+        .map(|&prior| {
+            let report = benchmark(&prior.apply_to(dictionary), answers, options, progress)?;
+            Ok((prior, report))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::WordN;
+
+    #[test]
+    fn benchmark_report() {
+        let words = [
+            "crane", "trace", "react", "caret", "brave", "grade", "slate",
+        ]
+        .into_iter()
+        .map(|w| WordN::try_from(w).unwrap())
+        .collect::<Vec<WordN<char, 5>>>();
+        let dictionary = Dictionary::new(words, vec![1.; 7]);
+        let answers = dictionary.answers.clone();
+        let options = BenchmarkOptions {
+            openers: vec![0, 6],
+            max_turns: 2,
+            ..Default::default()
+        };
+
+        let progress = Progress::new();
+        let report = benchmark(&dictionary, &answers, &options, &progress).unwrap();
+        assert_eq!((14, 14), (progress.done(), progress.total()));
+
+        assert_eq!(14, report.stats.games);
+        assert_eq!(14, report.stats.histogram.values().sum::<usize>());
+        assert_eq!(
+            vec!["crane", "slate"],
+            report.openers.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(7, report.openers["crane"].games);
+        assert_eq!(Some(&1), report.openers["crane"].histogram.get(&1));
+        assert_eq!(report.stats.max_turns, report.worst[0].turns);
+        assert_eq!(
+            report.stats.failures,
+            report.results.iter().filter(|r| r.turns > 2).count()
+        );
+        assert_eq!(15, report.to_csv().lines().count());
+
+        let comparison =
+            compare_priors(&dictionary, &answers, &Prior::ALL, &options, &progress).unwrap();
+        assert_eq!(Prior::ALL.len(), comparison.len());
+        assert!(comparison
+            .iter()
+            .all(|(_, report)| report.stats.games == 14));
+
+        progress.cancel();
+        assert_eq!(
+            Err(Cancelled),
+            benchmark(&dictionary, &answers, &options, &progress)
+        );
+    }
+}
@@ -1,12 +1,18 @@
+use std::fmt;
+use std::str::FromStr;
+
 use nalgebra::{DVector, Scalar};
 use num::One;
 use num_traits::Float;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use varpro::model::builder::error::ModelBuildError;
+use varpro::model::SeparableModel;
 use varpro::prelude::*;
 use varpro::solvers::levmar::{LevMarProblemBuilder, LevMarSolver};
 
+/// Parameters of the expected number of turns left, `c·ln(a0·(x + a1))`,
+/// as a function of the uncertainty `x` in bits
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Calibration {
     pub c: f64,
@@ -14,6 +20,8 @@ pub struct Calibration {
     pub a1: f64,
 }
 
+/// Fitted to the simulations of an early version of the solver,
+/// the `calibrate` command of the binary fits a new one for a given word list
 impl Default for Calibration {
     fn default() -> Self {
         Self {
@@ -24,8 +32,28 @@ impl Default for Calibration {
     }
 }
 
-pub fn bounded_log_c(x: f64, Calibration { c, a0, a1 }: Calibration) -> f64 {
-    let val = c * log(x, a0, a1);
+/// Expected number of turns left as a function of the uncertainty `x` in bits
+pub trait TurnsModel {
+    /// Turns left as fitted, possibly less than one
+    fn turns_left(&self, x: f64) -> f64;
+
+    /// Number of the fitted parameters, penalized by the AICc
+    fn parameters(&self) -> usize;
+}
+
+impl TurnsModel for Calibration {
+    fn turns_left(&self, x: f64) -> f64 {
+        self.c * log(x, self.a0, self.a1)
+    }
+
+    fn parameters(&self) -> usize {
+        3
+    }
+}
+
+/// Turns left estimated by the `model`, at least one
+pub fn bounded_turns<M: TurnsModel + ?Sized>(x: f64, model: &M) -> f64 {
+    let val = model.turns_left(x);
 
     if val > 1. {
         val
@@ -34,6 +62,10 @@ pub fn bounded_log_c(x: f64, Calibration { c, a0, a1 }: Calibration) -> f64 {
     }
 }
 
+pub fn bounded_log_c(x: f64, calibration: Calibration) -> f64 {
+    bounded_turns(x, &calibration)
+}
+
 pub fn log<S: Scalar + Float>(x: S, a0: S, a1: S) -> S {
     (a0 * (x + a1)).ln()
 }
@@ -64,25 +96,24 @@ pub enum FitError {
     NoFitResults,
 }
 
-pub fn fit(data: Vec<(f64, f64)>, weights: Option<Vec<f64>>) -> Result<Calibration, FitError> {
-    let (x, y): (Vec<_>, Vec<_>) = data.into_iter().unzip();
-    let Calibration { a0, a1, .. } = Calibration::default();
-
-    let model = SeparableModelBuilder::<f64>::new(&["a0", "a1"])
-        .function(&["a0", "a1"], log_v)
-        .partial_deriv("a0", log_da0)
-        .partial_deriv("a1", log_da1)
-        .build()
-        .map_err(FitError::from)?;
+/// Fits the nonlinear parameters of the `model` starting from `initial_guess`,
+/// returns them along with the linear coefficients
+fn solve(
+    model: &SeparableModel<f64>,
+    data: &[(f64, f64)],
+    weights: Option<Vec<f64>>,
+    initial_guess: &[f64],
+) -> Result<(Vec<f64>, Vec<f64>), FitError> {
+    let (x, y): (Vec<_>, Vec<_>) = data.iter().copied().unzip();
 
-    let problem = LevMarProblemBuilder::new().model(&model).x(x).y(y);
+    let problem = LevMarProblemBuilder::new().model(model).x(x).y(y);
     let problem = if let Some(weights) = weights {
         problem.weights(weights)
     } else {
         problem
     };
     let problem = problem
-        .initial_guess(&[a0, a1])
+        .initial_guess(initial_guess)
         .build()
         .map_err(|_| FitError::ProblemBuildUnsuccessful)?;
 
@@ -96,9 +127,604 @@ pub fn fit(data: Vec<(f64, f64)>, weights: Option<Vec<f64>>) -> Result<Calibrati
         .linear_coefficients()
         .ok_or(FitError::NoFitResults)?;
 
+    Ok((alpha.iter().copied().collect(), c.iter().copied().collect()))
+}
+
+pub fn fit(data: Vec<(f64, f64)>, weights: Option<Vec<f64>>) -> Result<Calibration, FitError> {
+    let Calibration { a0, a1, .. } = Calibration::default();
+
+    let model = SeparableModelBuilder::<f64>::new(&["a0", "a1"])
+        .function(&["a0", "a1"], log_v)
+        .partial_deriv("a0", log_da0)
+        .partial_deriv("a1", log_da1)
+        .build()
+        .map_err(FitError::from)?;
+
+    let (alpha, c) = solve(&model, &data, weights, &[a0, a1])?;
     Ok(Calibration {
         c: c[0],
         a0: alpha[0],
         a1: alpha[1],
     })
 }
+
+/// Power of the uncertainty times its log, `c·(x + 1)^r·ln(x + 1)`
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PowerLog {
+    pub c: f64,
+    pub r: f64,
+}
+
+impl TurnsModel for PowerLog {
+    fn turns_left(&self, x: f64) -> f64 {
+        self.c * power_log(x, self.r)
+    }
+
+    fn parameters(&self) -> usize {
+        2
+    }
+}
+
+pub fn power_log<S: Scalar + Float>(x: S, r: S) -> S {
+    let x = x + One::one();
+    x.powf(r) * x.ln()
+}
+
+fn power_log_v<S: Scalar + Float>(x: &DVector<S>, r: S) -> DVector<S> {
+    x.map(|x| power_log(x, r))
+}
+
+fn power_log_dr<S: Scalar + Float>(x: &DVector<S>, r: S) -> DVector<S> {
+    let one: S = One::one();
+    x.map(|x| power_log(x, r) * (x + one).ln())
+}
+
+impl PowerLog {
+    pub fn fit(data: &[(f64, f64)]) -> Result<Self, FitError> {
+        let model = SeparableModelBuilder::<f64>::new(&["r"])
+            .function(&["r"], power_log_v)
+            .partial_deriv("r", power_log_dr)
+            .build()
+            .map_err(FitError::from)?;
+
+        let (alpha, c) = solve(&model, data, None, &[-0.5])?;
+        Ok(Self {
+            c: c[0],
+            r: alpha[0],
+        })
+    }
+}
+
+/// Two lines meeting at the `knot`, `a + b·x + d·max(x - knot, 0)`
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PiecewiseLinear {
+    pub a: f64,
+    pub b: f64,
+    pub d: f64,
+    pub knot: f64,
+}
+
+impl TurnsModel for PiecewiseLinear {
+    fn turns_left(&self, x: f64) -> f64 {
+        self.a + self.b * x + self.d * hinge(x, self.knot)
+    }
+
+    fn parameters(&self) -> usize {
+        4
+    }
+}
+
+fn hinge<S: Scalar + Float>(x: S, knot: S) -> S {
+    (x - knot).max(S::zero())
+}
+
+fn hinge_v<S: Scalar + Float>(x: &DVector<S>, knot: S) -> DVector<S> {
+    x.map(|x| hinge(x, knot))
+}
+
+fn hinge_dknot<S: Scalar + Float>(x: &DVector<S>, knot: S) -> DVector<S> {
+    x.map(|x| if x > knot { -S::one() } else { S::zero() })
+}
+
+impl PiecewiseLinear {
+    pub fn fit(data: &[(f64, f64)]) -> Result<Self, FitError> {
+        let model = SeparableModelBuilder::<f64>::new(&["knot"])
+            .invariant_function(|x| x.map(|_| 1.))
+            .invariant_function(|x| x.clone())
+            .function(&["knot"], hinge_v)
+            .partial_deriv("knot", hinge_dknot)
+            .build()
+            .map_err(FitError::from)?;
+
+        // starts in the middle, so that the hinge isn't zero for all the points
+        let mean = data.iter().map(|&(x, _)| x).sum::<f64>() / data.len().max(1) as f64;
+        let (alpha, c) = solve(&model, data, None, &[mean])?;
+        Ok(Self {
+            a: c[0],
+            b: c[1],
+            d: c[2],
+            knot: alpha[0],
+        })
+    }
+}
+
+/// Non-decreasing turns left through the points, linearly interpolated between them
+/// and constant past the first and the last one
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Isotonic {
+    /// Uncertainties, sorted
+    pub xs: Vec<f64>,
+    pub ys: Vec<f64>,
+}
+
+impl TurnsModel for Isotonic {
+    fn turns_left(&self, x: f64) -> f64 {
+        let i = self.xs.partition_point(|&x0| x0 < x);
+        match (i, self.ys.len()) {
+            (_, 0) => 1.,
+            (0, _) => self.ys[0],
+            (i, len) if i == len => self.ys[len - 1],
+            (i, _) => {
+                let (x0, x1) = (self.xs[i - 1], self.xs[i]);
+                let t = (x - x0) / (x1 - x0);
+                self.ys[i - 1] + t * (self.ys[i] - self.ys[i - 1])
+            }
+        }
+    }
+
+    /// The number of the distinct levels
+    fn parameters(&self) -> usize {
+        self.ys.len() - self.ys.windows(2).filter(|w| w[0] == w[1]).count()
+    }
+}
+
+impl Isotonic {
+    /// Least squares fit with the pool adjacent violators algorithm, there is
+    /// nothing nonlinear to fit with `varpro` here
+    pub fn fit(data: &[(f64, f64)]) -> Result<Self, FitError> {
+        if data.is_empty() {
+            return Err(FitError::NoFitResults);
+        }
+        let mut data = data.to_vec();
+        data.sort_by(|(x1, _), (x2, _)| x1.total_cmp(x2));
+
+        // pooled blocks of the points, as (mean, number of the points)
+        let mut blocks: Vec<(f64, usize)> = vec![];
+        for &(_, y) in &data {
+            let mut block = (y, 1);
+            while let Some(&(mean, len)) = blocks.last() {
+                if mean < block.0 {
+                    break;
+                }
+                blocks.pop();
+                let pooled = len + block.1;
+                block = (
+                    (mean * len as f64 + block.0 * block.1 as f64) / pooled as f64,
+                    pooled,
+                );
+            }
+            blocks.push(block);
+        }
+
+        Ok(Self {
+            xs: data.iter().map(|&(x, _)| x).collect(),
+            ys: blocks
+                .into_iter()
+                .flat_map(|(mean, len)| std::iter::repeat_n(mean, len))
+                .collect(),
+        })
+    }
+}
+
+/// The fit-able families of the expected turns models, selectable by name
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModelFamily {
+    #[default]
+    Log,
+    PowerLog,
+    PiecewiseLinear,
+    Isotonic,
+}
+
+impl ModelFamily {
+    pub const ALL: [ModelFamily; 4] = [
+        ModelFamily::Log,
+        ModelFamily::PowerLog,
+        ModelFamily::PiecewiseLinear,
+        ModelFamily::Isotonic,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ModelFamily::Log => "log",
+            ModelFamily::PowerLog => "power-log",
+            ModelFamily::PiecewiseLinear => "piecewise-linear",
+            ModelFamily::Isotonic => "isotonic",
+        }
+    }
+
+    pub fn fit(self, data: &[(f64, f64)]) -> Result<Model, FitError> {
+        Ok(match self {
+            ModelFamily::Log => Model::Log(fit(data.to_vec(), None)?),
+            ModelFamily::PowerLog => Model::PowerLog(PowerLog::fit(data)?),
+            ModelFamily::PiecewiseLinear => Model::PiecewiseLinear(PiecewiseLinear::fit(data)?),
+            ModelFamily::Isotonic => Model::Isotonic(Isotonic::fit(data)?),
+        })
+    }
+}
+
+impl fmt::Display for ModelFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown model \"{0}\", expected one of: log, power-log, piecewise-linear, isotonic")]
+pub struct ParseModelFamilyError(String);
+
+impl FromStr for ModelFamily {
+    type Err = ParseModelFamilyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ModelFamily::ALL
+            .into_iter()
+            .find(|family| family.name() == s)
+            .ok_or_else(|| ParseModelFamilyError(s.to_string()))
+    }
+}
+
+/// Expected turns model of any of the families
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Model {
+    Log(Calibration),
+    PowerLog(PowerLog),
+    PiecewiseLinear(PiecewiseLinear),
+    Isotonic(Isotonic),
+}
+
+impl Model {
+    pub fn family(&self) -> ModelFamily {
+        match self {
+            Model::Log(_) => ModelFamily::Log,
+            Model::PowerLog(_) => ModelFamily::PowerLog,
+            Model::PiecewiseLinear(_) => ModelFamily::PiecewiseLinear,
+            Model::Isotonic(_) => ModelFamily::Isotonic,
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Model::Log(Calibration::default())
+    }
+}
+
+impl From<Calibration> for Model {
+    fn from(calibration: Calibration) -> Self {
+        Model::Log(calibration)
+    }
+}
+
+impl TurnsModel for Model {
+    fn turns_left(&self, x: f64) -> f64 {
+        match self {
+            Model::Log(model) => model.turns_left(x),
+            Model::PowerLog(model) => model.turns_left(x),
+            Model::PiecewiseLinear(model) => model.turns_left(x),
+            Model::Isotonic(model) => model.turns_left(x),
+        }
+    }
+
+    fn parameters(&self) -> usize {
+        match self {
+            Model::Log(model) => model.parameters(),
+            Model::PowerLog(model) => model.parameters(),
+            Model::PiecewiseLinear(model) => model.parameters(),
+            Model::Isotonic(model) => model.parameters(),
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Model::Log(Calibration { c, a0, a1 }) => {
+                write!(f, "{c:.3} ln({a0:.3} (x + {a1:.3}))")
+            }
+            Model::PowerLog(PowerLog { c, r }) => write!(f, "{c:.3} (x + 1)^{r:.3} ln(x + 1)"),
+            Model::PiecewiseLinear(PiecewiseLinear { a, b, d, knot }) => {
+                write!(f, "{a:.3} + {b:.3} x + {d:.3} max(x - {knot:.3}, 0)")
+            }
+            Model::Isotonic(model) => write!(f, "isotonic, {} levels", model.parameters()),
+        }
+    }
+}
+
+/// Mean square of the residuals the information criterion treats as an exact fit
+const MIN_MSE: f64 = 1e-12;
+
+/// How well a model fits the points it was fitted to
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FitStats {
+    /// Fitted value subtracted from the data for each of the points
+    pub residuals: Vec<f64>,
+    /// Root mean square of the residuals
+    pub rmse: f64,
+    /// Coefficient of determination
+    pub r_squared: f64,
+}
+
+impl FitStats {
+    pub fn new<M: TurnsModel + ?Sized>(model: &M, data: &[(f64, f64)]) -> Self {
+        let residuals = data
+            .iter()
+            .map(|&(x, y)| y - model.turns_left(x))
+            .collect::<Vec<_>>();
+
+        let len = data.len().max(1) as f64;
+        let mean = data.iter().map(|&(_, y)| y).sum::<f64>() / len;
+        let total = data.iter().map(|&(_, y)| (y - mean).powi(2)).sum::<f64>();
+        let residual = residuals.iter().map(|r| r * r).sum::<f64>();
+
+        Self {
+            rmse: (residual / len).sqrt(),
+            r_squared: if total > 0. {
+                1. - residual / total
+            } else {
+                1.
+            },
+            residuals,
+        }
+    }
+
+    /// Akaike information criterion of a least squares fit with the given number
+    /// of `parameters`, corrected for the small samples (AICc), the lower the better.
+    /// Infinite for the models with at least as many parameters as the points but one,
+    /// which can pass through all of them.
+    pub fn aic(&self, parameters: usize) -> f64 {
+        let len = self.residuals.len();
+        if parameters + 1 >= len {
+            return f64::INFINITY;
+        }
+
+        let (n, k) = (len as f64, parameters as f64);
+        // an exact fit would be infinitely good otherwise
+        let mse = (self.rmse * self.rmse).max(MIN_MSE);
+        n * mse.ln() + 2. * k + 2. * k * (k + 1.) / (n - k - 1.)
+    }
+}
+
+/// Bins the `(uncertainty, turns left, weight)` points by the uncertainty, `bins_per_bit` bins
+/// for each bit. Returns the bounds of the non-empty bins along with the weighted mean turns left.
+pub fn bin_data(data: &[(f64, f64, f64)], bins_per_bit: usize) -> Vec<((f64, f64), f64)> {
+    let x_max = 1. + data.iter().map(|&(x, _, _)| x).fold(4., f64::max);
+    let bins_len = bins_per_bit * x_max.ceil() as usize;
+
+    let mut bins = vec![(0., 0.); bins_len];
+    for &(x, y, weight) in data {
+        if let Some((weighted_sum, norm)) = bins.get_mut((x * bins_per_bit as f64).floor() as usize)
+        {
+            *weighted_sum += weight * y;
+            *norm += weight;
+        }
+    }
+
+    let width = 1. / bins_per_bit as f64;
+    bins.into_iter()
+        .enumerate()
+        .filter(|&(_, (_, norm))| norm > 0.)
+        .map(|(i, (weighted_sum, norm))| {
+            (
+                (i as f64 * width, (i + 1) as f64 * width),
+                weighted_sum / norm,
+            )
+        })
+        .collect()
+}
+
+fn bin_centers(bins: &[((f64, f64), f64)]) -> Vec<(f64, f64)> {
+    bins.iter()
+        .map(|&((x0, x1), y)| (0.5 * (x0 + x1), y))
+        .collect()
+}
+
+/// Fits the calibration to the centers of the `bins` returned by [`bin_data`]
+pub fn fit_bins(bins: &[((f64, f64), f64)]) -> Result<(Calibration, FitStats), FitError> {
+    let data = bin_centers(bins);
+    let calibration = fit(data.clone(), None)?;
+    Ok((calibration, FitStats::new(&calibration, &data)))
+}
+
+/// Model of one of the families fitted to the bins, along with the goodness of fit
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelComparison {
+    pub model: Model,
+    pub stats: FitStats,
+    pub aic: f64,
+}
+
+/// Fits every family to the centers of the `bins` returned by [`bin_data`], the lowest AICc first.
+/// The families failing to fit are returned separately.
+pub fn compare_models(
+    bins: &[((f64, f64), f64)],
+) -> (Vec<ModelComparison>, Vec<(ModelFamily, FitError)>) {
+    let data = bin_centers(bins);
+
+    let mut comparisons = vec![];
+    let mut failures = vec![];
+    for family in ModelFamily::ALL {
+        match family.fit(&data) {
+            Ok(model) => {
+                let stats = FitStats::new(&model, &data);
+                let aic = stats.aic(model.parameters());
+                comparisons.push(ModelComparison { model, stats, aic });
+            }
+            Err(err) => failures.push((family, err)),
+        }
+    }
+
+    comparisons.sort_by(|c1, c2| c1.aic.total_cmp(&c2.aic));
+    (comparisons, failures)
+}
+
+/// Calibration saved by the `calibrate` command, along with how it was fitted.
+/// A bare [`Calibration`] is a valid file as well, and the file is a valid [`Calibration`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationFile {
+    #[serde(flatten)]
+    pub calibration: Calibration,
+    /// Number of the simulated games
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bins_per_bit: Option<usize>,
+    /// Model chosen among the families, the log one of `calibration` if missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<Model>,
+    /// Goodness of fit of the chosen model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fit: Option<FitStats>,
+}
+
+impl CalibrationFile {
+    pub fn model(&self) -> Model {
+        self.model.clone().unwrap_or(Model::Log(self.calibration))
+    }
+}
+
+impl From<Calibration> for CalibrationFile {
+    fn from(calibration: Calibration) -> Self {
+        Self {
+            calibration,
+            samples: None,
+            bins_per_bit: None,
+            model: None,
+            fit: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_binned_data() {
+        let calibration = Calibration::default();
+        let data = (0..200)
+            .map(|i| {
+                let x = i as f64 / 20.;
+                (
+                    x,
+                    calibration.c * log(x, calibration.a0, calibration.a1),
+                    1.,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let bins = bin_data(&data, 4);
+        assert_eq!(40, bins.len());
+        let (fitted, stats) = fit_bins(&bins).unwrap();
+
+        assert!(stats.r_squared > 0.99);
+        assert_eq!(bins.len(), stats.residuals.len());
+        assert!((fitted.c - calibration.c).abs() < 0.1);
+
+        let file = CalibrationFile {
+            fit: Some(stats),
+            ..fitted.into()
+        };
+        // the file loads as a bare calibration, and the other way around
+        let loaded: Calibration =
+            serde_json::from_str(&serde_json::to_string(&file).unwrap()).unwrap();
+        let bare: CalibrationFile =
+            serde_json::from_str(&serde_json::to_string(&fitted).unwrap()).unwrap();
+        assert_eq!(loaded, bare.calibration);
+        assert!((loaded.a0 - fitted.a0).abs() < 1e-12);
+        assert_eq!(None, bare.fit);
+    }
+
+    #[test]
+    fn compare_model_families() {
+        // a bit of deterministic noise, so that the isotonic fit has to pool
+        let data = (0..400)
+            .map(|i| {
+                let x = i as f64 / 40.;
+                (x, 1. + 0.5 * x - 0.02 * x * x + 0.1 * (7. * x).sin(), 1.)
+            })
+            .collect::<Vec<_>>();
+
+        let bins = bin_data(&data, 2);
+        let (comparisons, failures) = compare_models(&bins);
+        assert!(failures.is_empty(), "{failures:?}");
+        assert_eq!(ModelFamily::ALL.len(), comparisons.len());
+        assert!(comparisons.windows(2).all(|w| w[0].aic <= w[1].aic));
+        for comparison in &comparisons {
+            assert!(comparison.stats.r_squared > 0.9, "{comparison:?}");
+        }
+
+        let isotonic = ModelFamily::Isotonic.fit(&bin_centers(&bins)).unwrap();
+        let turns = (0..100)
+            .map(|i| isotonic.turns_left(i as f64 / 8.))
+            .collect::<Vec<_>>();
+        assert!(turns.windows(2).all(|w| w[0] <= w[1]));
+        assert!(isotonic.parameters() < bins.len());
+
+        let (calibration, _) = fit_bins(&bins).unwrap();
+        let file = CalibrationFile {
+            model: Some(isotonic.clone()),
+            ..calibration.into()
+        };
+        let loaded: CalibrationFile =
+            serde_json::from_str(&serde_json::to_string(&file).unwrap()).unwrap();
+        // JSON doesn't roundtrip the floats exactly
+        let loaded = loaded.model();
+        assert_eq!(ModelFamily::Isotonic, loaded.family());
+        assert!((isotonic.turns_left(5.) - loaded.turns_left(5.)).abs() < 1e-12);
+        assert_eq!(
+            Model::Log(calibration),
+            CalibrationFile::from(calibration).model()
+        );
+        assert_eq!(
+            Ok(ModelFamily::PowerLog),
+            "power-log".parse().map_err(|_| ())
+        );
+    }
+
+    #[test]
+    fn compare_models_on_monotone_data() {
+        // strictly increasing, the isotonic fit pools nothing and passes through every point
+        let data = (0..80)
+            .map(|i| {
+                let x = i as f64 / 8.;
+                (x, 1. + (x + 1.).ln(), 1.)
+            })
+            .collect::<Vec<_>>();
+
+        let bins = bin_data(&data, 4);
+        let (comparisons, failures) = compare_models(&bins);
+        assert!(failures.is_empty(), "{failures:?}");
+
+        let isotonic = comparisons
+            .iter()
+            .find(|comparison| comparison.model.family() == ModelFamily::Isotonic)
+            .unwrap();
+        assert_eq!(bins.len(), isotonic.model.parameters());
+        assert!(isotonic.stats.rmse < 1e-9);
+        assert_eq!(f64::INFINITY, isotonic.aic);
+        assert_eq!(
+            ModelFamily::Isotonic,
+            comparisons.last().unwrap().model.family()
+        );
+        assert!(comparisons[0].aic.is_finite());
+
+        // an exact fit with fewer parameters is scored by the floor, not minus infinity
+        let exact = FitStats {
+            residuals: vec![0.; bins.len()],
+            rmse: 0.,
+            r_squared: 1.,
+        };
+        assert!(exact.aic(2).is_finite());
+    }
+}
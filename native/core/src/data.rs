@@ -1,57 +1,599 @@
+use crate::priors::Prior;
 use crate::structs::WordError;
-use crate::structs::{Dictionary, WordN};
+use crate::structs::{AnyDictionary, Dictionary, WordN, WORD_LENGTHS};
+use fxhash::FxHashMap;
+use std::fmt;
 use std::io::{self, BufRead};
 use std::num::ParseFloatError;
 use std::{fs::File, path::Path};
 use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Debug, Error)]
 pub enum LoadError {
     #[error("Error reading file")]
     IOError(#[from] io::Error),
+    /// The word list has no words
     #[error("Empty line")]
     EmptyLine,
     #[error("Error parsing word")]
     ParseWordError(#[from] WordError),
     #[error("Error parsing probability")]
     ParseFloatError(#[from] ParseFloatError),
+    #[error("Error parsing answer flag: \"{0}\", expected one of 1, 0, true, false")]
+    ParseAnswerFlagError(String),
+    #[error("Unsupported word length: {0}, expected one of {WORD_LENGTHS:?}")]
+    UnsupportedWordLength(usize),
+    #[error("Line {line}: {source}")]
+    Line { line: usize, source: Box<LoadError> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadWarning {
+    Duplicate {
+        word: String,
+        line: usize,
+        first_line: usize,
+    },
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadWarning::Duplicate {
+                word,
+                line,
+                first_line,
+            } => write!(
+                f,
+                "Line {line}: duplicate of \"{word}\" from line {first_line}, skipped"
+            ),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Delimiter {
+    /// Taken from the first line: tab, comma or else any whitespace
+    Auto,
+    Comma,
+    Tab,
+    Whitespace,
+}
+
+impl Delimiter {
+    fn detect(line: &str) -> Self {
+        if line.contains('\t') {
+            Delimiter::Tab
+        } else if line.contains(',') {
+            Delimiter::Comma
+        } else {
+            Delimiter::Whitespace
+        }
+    }
+
+    fn split(self, line: &str) -> Vec<&str> {
+        match self {
+            Delimiter::Comma => line.split(',').map(str::trim).collect(),
+            Delimiter::Tab => line.split('\t').map(str::trim).collect(),
+            Delimiter::Whitespace | Delimiter::Auto => line.split_whitespace().collect(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Header {
+    /// The first line is a header if its word column is named "word"
+    /// or its second column isn't a number
+    Auto,
+    Present,
+    Absent,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LoadOptions {
+    pub delimiter: Delimiter,
+    pub header: Header,
+    /// Turns the second column, raw counts or probabilities, into the probabilities
+    pub prior: Prior,
+    /// Composes the letters followed by combining diacritics, e.g. "e\u{301}" into "é",
+    /// by the Unicode normalization form C
+    pub normalize: bool,
+    pub lowercase: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: Delimiter::Auto,
+            header: Header::Auto,
+            prior: Prior::Raw,
+            normalize: true,
+            lowercase: true,
+        }
+    }
+}
+
+/// Non-empty line of the word list
+struct Record {
+    line: usize,
+    word: String,
+    weight: Option<f64>,
+    answer: bool,
+}
+
+fn parse_record(
+    fields: &[&str],
+    options: &LoadOptions,
+) -> Result<(String, Option<f64>, bool), LoadError> {
+    let mut word = fields[0].to_string();
+    if options.lowercase {
+        word = word.to_lowercase();
+    }
+    if options.normalize {
+        word = word.nfc().collect();
+    }
+    let weight = match fields.get(1) {
+        Some(weight_str) if !weight_str.is_empty() => Some(weight_str.parse::<f64>()?),
+        _ => None,
+    };
+    let answer = match fields.get(2) {
+        Some(flag_str) => parse_answer_flag(flag_str)?,
+        None => true,
+    };
+
+    Ok((word, weight, answer))
+}
+
+fn is_header(fields: &[&str]) -> bool {
+    matches!(fields[0].to_lowercase().as_str(), "word" | "words")
+        || fields
+            .get(1)
+            .is_some_and(|weight_str| !weight_str.is_empty() && weight_str.parse::<f64>().is_err())
+}
+
+/// Parses the lines in the `word[,weight[,answer]]` format, skipping the blank lines,
+/// `#` comments and the header
+fn parse_records<'a, I>(lines: I, options: &LoadOptions) -> Result<Vec<Record>, LoadError>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let mut delimiter = options.delimiter;
+    let mut header = options.header;
+    let mut records = vec![];
+
+    for (i, line) in lines.enumerate() {
+        let line_number = i + 1;
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if delimiter == Delimiter::Auto {
+            delimiter = Delimiter::detect(line);
+        }
+        let fields = delimiter.split(line);
+
+        if header != Header::Absent {
+            let skip = header == Header::Present || is_header(&fields);
+            header = Header::Absent;
+            if skip {
+                continue;
+            }
+        }
+
+        let (word, weight, answer) =
+            parse_record(&fields, options).map_err(|err| LoadError::Line {
+                line: line_number,
+                source: Box::new(err),
+            })?;
+        records.push(Record {
+            line: line_number,
+            word,
+            weight,
+            answer,
+        });
+    }
+
+    Ok(records)
+}
+
+/// Words of one list, deduplicated, with their weights and answer flags
+struct Entries<const N: usize> {
+    words: Vec<WordN<char, N>>,
+    weights: Vec<f64>,
+    answers: Vec<bool>,
+}
+
+fn parse_entries<const N: usize>(
+    records: Vec<Record>,
+    warnings: &mut Vec<LoadWarning>,
+) -> Result<Entries<N>, LoadError> {
+    let mut first_lines = FxHashMap::<String, usize>::default();
+    let mut words = vec![];
+    let mut weights = vec![];
+    let mut answers = vec![];
+
+    for record in records {
+        if let Some(&first_line) = first_lines.get(&record.word) {
+            warnings.push(LoadWarning::Duplicate {
+                word: record.word,
+                line: record.line,
+                first_line,
+            });
+            continue;
+        }
+
+        let word = WordN::try_from(record.word.as_str()).map_err(|err| LoadError::Line {
+            line: record.line,
+            source: Box::new(err.into()),
+        })?;
+        first_lines.insert(record.word, record.line);
+        words.push(word);
+        weights.push(record.weight.unwrap_or(1.));
+        answers.push(record.answer);
+    }
+
+    Ok(Entries {
+        words,
+        weights,
+        answers,
+    })
+}
+
+fn read_lines<P: AsRef<Path>>(filename: P) -> Result<Vec<String>, LoadError> {
+    let file = File::open(filename)?;
+    Ok(io::BufReader::new(file).lines().collect::<Result<_, _>>()?)
 }
 
 pub fn load_words<P, const N: usize>(filename: P) -> Result<Dictionary<N>, LoadError>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(filename)?;
-    let lines: Vec<_> = io::BufReader::new(file).lines().collect::<Result<_, _>>()?;
+    let lines = read_lines(filename)?;
     parse_words(lines.iter().map(|s| s.as_ref()))
 }
 
+pub fn load_words_with_answers<P, const N: usize>(
+    guesses_filename: P,
+    answers_filename: P,
+) -> Result<Dictionary<N>, LoadError>
+where
+    P: AsRef<Path>,
+{
+    let guesses_lines = read_lines(guesses_filename)?;
+    let answers_lines = read_lines(answers_filename)?;
+    parse_words_with_answers(
+        guesses_lines.iter().map(|s| s.as_ref()),
+        answers_lines.iter().map(|s| s.as_ref()),
+    )
+}
+
+pub fn load_words_any<P>(filename: P) -> Result<AnyDictionary, LoadError>
+where
+    P: AsRef<Path>,
+{
+    let (dictionary, _) = load_words_any_with(filename, None, &LoadOptions::default())?;
+    Ok(dictionary)
+}
+
+pub fn load_words_with_answers_any<P>(
+    guesses_filename: P,
+    answers_filename: P,
+) -> Result<AnyDictionary, LoadError>
+where
+    P: AsRef<Path>,
+{
+    let (dictionary, _) = load_words_any_with(
+        guesses_filename,
+        Some(answers_filename),
+        &LoadOptions::default(),
+    )?;
+    Ok(dictionary)
+}
+
+/// Loads the words list, along with the separate answers list if given,
+/// returns the warnings about the skipped lines
+pub fn load_words_any_with<P>(
+    guesses_filename: P,
+    answers_filename: Option<P>,
+    options: &LoadOptions,
+) -> Result<(AnyDictionary, Vec<LoadWarning>), LoadError>
+where
+    P: AsRef<Path>,
+{
+    let guesses_lines = read_lines(guesses_filename)?;
+    let answers_lines = answers_filename.map(read_lines).transpose()?;
+    parse_words_any_with(
+        guesses_lines.iter().map(|s| s.as_ref()),
+        answers_lines
+            .as_ref()
+            .map(|lines| lines.iter().map(|s| s.as_ref())),
+        options,
+    )
+}
+
+/// Returns the length of the first word in the list
+pub fn detect_word_length<'a, I>(lines: I) -> Result<usize, LoadError>
+where
+    I: Iterator<Item = &'a str>,
+{
+    detect_word_length_with(lines, &LoadOptions::default())
+}
+
+pub fn detect_word_length_with<'a, I>(lines: I, options: &LoadOptions) -> Result<usize, LoadError>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let records = parse_records(lines, options)?;
+    let first = records.first().ok_or(LoadError::EmptyLine)?;
+    Ok(first.word.chars().count())
+}
+
+/// Parses the words list with the word length taken from its first word
+pub fn parse_words_any<'a, I>(lines: I) -> Result<AnyDictionary, LoadError>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let (dictionary, _) = parse_words_any_with(lines, None::<I>, &LoadOptions::default())?;
+    Ok(dictionary)
+}
+
+/// Same as [`parse_words_with_answers`], with the word length taken from the first guess
+pub fn parse_words_with_answers_any<'a, I, J>(
+    guesses_lines: I,
+    answers_lines: J,
+) -> Result<AnyDictionary, LoadError>
+where
+    I: Iterator<Item = &'a str>,
+    J: Iterator<Item = &'a str>,
+{
+    let (dictionary, _) =
+        parse_words_any_with(guesses_lines, Some(answers_lines), &LoadOptions::default())?;
+    Ok(dictionary)
+}
+
+/// Parses the words list, along with the separate answers list if given,
+/// with the word length taken from the first guess
+pub fn parse_words_any_with<'a, I, J>(
+    guesses_lines: I,
+    answers_lines: Option<J>,
+    options: &LoadOptions,
+) -> Result<(AnyDictionary, Vec<LoadWarning>), LoadError>
+where
+    I: Iterator<Item = &'a str>,
+    J: Iterator<Item = &'a str>,
+{
+    let guesses = parse_records(guesses_lines, options)?;
+    let answers = answers_lines
+        .map(|lines| parse_records(lines, options))
+        .transpose()?;
+    let word_length = guesses
+        .first()
+        .ok_or(LoadError::EmptyLine)?
+        .word
+        .chars()
+        .count();
+
+    Ok(match word_length {
+        4 => {
+            let (dictionary, warnings) = dictionary_from_records(guesses, answers, options)?;
+            (AnyDictionary::Four(dictionary), warnings)
+        }
+        5 => {
+            let (dictionary, warnings) = dictionary_from_records(guesses, answers, options)?;
+            (AnyDictionary::Five(dictionary), warnings)
+        }
+        6 => {
+            let (dictionary, warnings) = dictionary_from_records(guesses, answers, options)?;
+            (AnyDictionary::Six(dictionary), warnings)
+        }
+        7 => {
+            let (dictionary, warnings) = dictionary_from_records(guesses, answers, options)?;
+            (AnyDictionary::Seven(dictionary), warnings)
+        }
+        _ => return Err(LoadError::UnsupportedWordLength(word_length)),
+    })
+}
+
+fn parse_answer_flag(flag_str: &str) -> Result<bool, LoadError> {
+    match flag_str.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" => Ok(true),
+        "0" | "false" => Ok(false),
+        _ => Err(LoadError::ParseAnswerFlagError(flag_str.to_string())),
+    }
+}
+
 pub fn parse_words<'a, I, const N: usize>(lines: I) -> Result<Dictionary<N>, LoadError>
 where
     I: Iterator<Item = &'a str>,
 {
-    let words_with_probs = lines
-        .map(|l| {
-            let mut split = l.split(",");
-            split
-                .next()
-                .ok_or_else(|| LoadError::EmptyLine)
-                .and_then(|word_str| {
-                    let probability_str = split.next().unwrap_or("1.0");
-                    word_str
-                        .try_into()
-                        .map_err(|e: WordError| e.into())
-                        .and_then(|word: WordN<char, N>| {
-                            probability_str
-                                .parse::<f64>()
-                                .map_err(|e| e.into())
-                                .map(|probability| (word, probability))
-                        })
-                })
-        })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let (words, probabilities) = words_with_probs.into_iter().unzip();
-
-    Ok(Dictionary::new(words, probabilities))
+    let (dictionary, _) = parse_words_with(lines, &LoadOptions::default())?;
+    Ok(dictionary)
+}
+
+/// Parses the lines in the `word[,weight[,answer]]` format, where a missing weight
+/// defaults to 1.0 and a missing answer flag means the word can be an answer.
+/// Duplicated words are skipped with a warning.
+pub fn parse_words_with<'a, I, const N: usize>(
+    lines: I,
+    options: &LoadOptions,
+) -> Result<(Dictionary<N>, Vec<LoadWarning>), LoadError>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let records = parse_records(lines, options)?;
+    dictionary_from_records(records, None, options)
+}
+
+/// Builds a dictionary from a list of allowed guesses and a separate list of answers.
+/// Answers missing from the guesses list are added to the allowed guesses as well.
+/// Weights of the answers are taken from the answers list.
+pub fn parse_words_with_answers<'a, I, J, const N: usize>(
+    guesses_lines: I,
+    answers_lines: J,
+) -> Result<Dictionary<N>, LoadError>
+where
+    I: Iterator<Item = &'a str>,
+    J: Iterator<Item = &'a str>,
+{
+    let options = LoadOptions::default();
+    let guesses = parse_records(guesses_lines, &options)?;
+    let answers = parse_records(answers_lines, &options)?;
+    let (dictionary, _) = dictionary_from_records(guesses, Some(answers), &options)?;
+    Ok(dictionary)
+}
+
+fn dictionary_from_records<const N: usize>(
+    guesses: Vec<Record>,
+    answers: Option<Vec<Record>>,
+    options: &LoadOptions,
+) -> Result<(Dictionary<N>, Vec<LoadWarning>), LoadError> {
+    let mut warnings = vec![];
+    let guesses = parse_entries::<N>(guesses, &mut warnings)?;
+
+    let answers = match answers {
+        Some(answers) => parse_entries::<N>(answers, &mut warnings)?,
+        None => {
+            let answers = guesses
+                .answers
+                .iter()
+                .enumerate()
+                .filter(|(_, &answer)| answer)
+                .map(|(i, _)| i)
+                .collect();
+            let probabilities = options.prior.apply(&guesses.weights);
+            let dictionary = Dictionary::with_answers(guesses.words, probabilities, answers);
+            return Ok((dictionary, warnings));
+        }
+    };
+
+    let Entries {
+        mut words,
+        mut weights,
+        ..
+    } = guesses;
+    let mut positions = words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| (word.clone(), i))
+        .collect::<FxHashMap<_, _>>();
+
+    let mut answer_inds = vec![];
+    for (word, weight) in answers.words.into_iter().zip(answers.weights) {
+        let i = match positions.get(&word) {
+            Some(&i) => {
+                weights[i] = weight;
+                i
+            }
+            None => {
+                positions.insert(word.clone(), words.len());
+                words.push(word);
+                weights.push(weight);
+                words.len() - 1
+            }
+        };
+        answer_inds.push(i);
+    }
+
+    // the prior depends on all the weights, e.g. through their minimum or ranks
+    let probabilities = options.prior.apply(&weights);
+    Ok((
+        Dictionary::with_answers(words, probabilities, answer_inds),
+        warnings,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_flag_column() {
+        let dictionary: Dictionary<3> =
+            parse_words(["abc,0.5,1", "bcd,0.2,0", "cde", "def,1,true"].into_iter()).unwrap();
+
+        assert_eq!(vec![0, 2, 3], dictionary.answers);
+        assert_eq!(vec![0.5, 0.2, 1., 1.], dictionary.probabilities);
+        assert!(parse_words::<_, 3>(["abc,0.5,x"].into_iter()).is_err());
+    }
+
+    #[test]
+    fn separate_answers_list() {
+        let dictionary: Dictionary<3> = parse_words_with_answers(
+            ["abc", "bcd", "cde"].into_iter(),
+            ["cde,0.3", "xyz"].into_iter(),
+        )
+        .unwrap();
+
+        let words = dictionary
+            .words
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["abc", "bcd", "cde", "xyz"], words);
+        assert_eq!(vec![2, 3], dictionary.answers);
+        assert_eq!(vec![1., 1., 0.3, 1.], dictionary.probabilities);
+
+        let options = LoadOptions {
+            prior: Prior::Log,
+            ..Default::default()
+        };
+        let guesses = parse_records(["abc,10", "bcd,100"].into_iter(), &options).unwrap();
+        let answers = parse_records(["bcd,1000", "cde,1000"].into_iter(), &options).unwrap();
+        let (dictionary, _) =
+            dictionary_from_records::<3>(guesses, Some(answers), &options).unwrap();
+        let log = 100f64.ln() + 1.;
+        assert_eq!(vec![1., log, log], dictionary.probabilities);
+    }
+
+    #[test]
+    fn word_length_dispatch() {
+        let dictionary = parse_words_any(["lingo,0.5", "words", "drake"].into_iter()).unwrap();
+        assert_eq!(5, dictionary.word_length());
+
+        let dictionary = parse_words_any(["śląsko", "wielki"].into_iter()).unwrap();
+        assert!(matches!(dictionary, AnyDictionary::Six(_)));
+
+        assert!(parse_words_any(["abc", "bcd"].into_iter()).is_err());
+        assert!(parse_words_any(["abcd", "bcdef"].into_iter()).is_err());
+    }
+
+    #[test]
+    fn loader_options() {
+        let lines = [
+            "\u{feff}word\tcount",
+            "# comment",
+            "",
+            "Abc\t10",
+            "ca\u{301}b\t100",
+            "abc\t5",
+            "xyz\t0",
+            "",
+        ];
+        let options = LoadOptions {
+            prior: Prior::Log,
+            ..Default::default()
+        };
+        let (dictionary, warnings) = parse_words_with::<_, 3>(lines.into_iter(), &options).unwrap();
+
+        let words = dictionary
+            .words
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["abc", "cáb", "xyz"], words);
+        assert_eq!(vec![1., 10f64.ln() + 1., 0.], dictionary.probabilities);
+        assert_eq!(
+            vec![LoadWarning::Duplicate {
+                word: "abc".to_string(),
+                line: 6,
+                first_line: 4
+            }],
+            warnings
+        );
+
+        let err = parse_words::<_, 3>(["abc", "", "abcd"].into_iter()).unwrap_err();
+        assert!(matches!(err, LoadError::Line { line: 3, .. }));
+    }
 }
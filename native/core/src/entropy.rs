@@ -1,21 +1,23 @@
 use ndarray::Array1;
-use rayon::iter::IndexedParallelIterator;
 #[cfg(feature = "parallel")]
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use std::cmp::Ordering::Equal;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering::{self, Equal},
+    fmt,
+    str::FromStr,
+};
+use thiserror::Error;
 
 use crate::{
-    algo,
-    calibration::{bounded_log_c, Calibration},
-    structs::{Dictionary, EntropiesData},
+    algo::GuessHints,
+    calibration::{bounded_turns, Model, TurnsModel},
+    progress::Progress,
+    structs::{hint_matrix::HintMatrix, Dictionary, EntropiesData},
 };
 
 pub fn entropy(arr: Array1<f64>) -> f64 {
-    let arr = arr
-        .into_iter()
-        .filter(|&x| x > 0.)
-        .map(|x| x as f64)
-        .collect::<Array1<f64>>();
+    let arr = arr.into_iter().filter(|&x| x > 0.).collect::<Array1<f64>>();
 
     let arr_log = {
         let mut arr_log = arr.clone();
@@ -23,69 +25,296 @@ pub fn entropy(arr: Array1<f64>) -> f64 {
         arr_log
     };
 
-    -1. * (arr * arr_log).sum()
+    -(arr * arr_log).sum()
+}
+
+/// Entropy of the `answers` left, weighted by their probabilities.
+/// The answers with zero probability add nothing to it.
+pub fn uncertainty<const N: usize>(dictionary: &Dictionary<N>, answers: &[usize]) -> f64 {
+    let prob_norm: f64 = answers.iter().map(|&i| dictionary.probabilities[i]).sum();
+    answers
+        .iter()
+        .map(|&i| dictionary.probabilities[i] / prob_norm)
+        .filter(|&probability| probability > 0.)
+        .map(|probability| -probability * probability.log2())
+        .sum()
 }
 
+/// Calculates the hint distribution and its entropy for every guess in `guesses`
+/// (indices into `dictionary.words`) over the `possible_answers`
 pub fn calculate_entropies<const N: usize>(
     dictionary: &Dictionary<N>,
+    guesses: &[usize],
     possible_answers: &[usize],
-) -> Vec<EntropiesData<N>> {
-    let prob_norm: f64 = possible_answers
-        .iter()
-        .map(|&i| dictionary.probabilities[i])
-        .sum();
-    let guess_words_bytes = &dictionary.words_bytes;
+) -> Vec<(usize, EntropiesData)> {
+    calculate_entropies_with_progress(dictionary, guesses, possible_answers, &Progress::new())
+        .expect("Calculation can't be cancelled without the progress")
+}
+
+/// Same as [`calculate_entropies`], reporting each of the guesses done to the `progress`.
+/// Returns `None` if it was cancelled.
+pub fn calculate_entropies_with_progress<const N: usize>(
+    dictionary: &Dictionary<N>,
+    guesses: &[usize],
+    possible_answers: &[usize],
+    progress: &Progress,
+) -> Option<Vec<(usize, EntropiesData)>> {
+    entropies_with_hints(dictionary, None, guesses, possible_answers, progress, true)
+}
+
+/// Same as [`calculate_entropies`], with the hints looked up in the precomputed `matrix`
+pub fn calculate_entropies_with_matrix<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
+    guesses: &[usize],
+    possible_answers: &[usize],
+) -> Vec<(usize, EntropiesData)> {
+    calculate_entropies_with_matrix_and_progress(
+        dictionary,
+        matrix,
+        guesses,
+        possible_answers,
+        &Progress::new(),
+    )
+    .expect("Calculation can't be cancelled without the progress")
+}
+
+/// Same as [`calculate_entropies_with_progress`], with the hints looked up in the `matrix`
+pub fn calculate_entropies_with_matrix_and_progress<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
+    guesses: &[usize],
+    possible_answers: &[usize],
+    progress: &Progress,
+) -> Option<Vec<(usize, EntropiesData)>> {
+    entropies_with_hints(
+        dictionary,
+        Some(matrix),
+        guesses,
+        possible_answers,
+        progress,
+        true,
+    )
+}
+
+/// Same as [`calculate_entropies`], or [`calculate_entropies_with_matrix`] with a `matrix`,
+/// leaving the probabilities of the hints empty. Enough for scoring the guesses
+/// and cheaper, the distribution of every guess isn't allocated.
+pub fn guess_summaries<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: Option<&HintMatrix>,
+    guesses: &[usize],
+    possible_answers: &[usize],
+) -> Vec<(usize, EntropiesData)> {
+    guess_summaries_with_progress(
+        dictionary,
+        matrix,
+        guesses,
+        possible_answers,
+        &Progress::new(),
+    )
+    .expect("Calculation can't be cancelled without the progress")
+}
+
+/// Same as [`guess_summaries`], reporting each of the guesses done to the `progress`.
+/// Returns `None` if it was cancelled.
+pub fn guess_summaries_with_progress<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: Option<&HintMatrix>,
+    guesses: &[usize],
+    possible_answers: &[usize],
+    progress: &Progress,
+) -> Option<Vec<(usize, EntropiesData)>> {
+    entropies_with_hints(
+        dictionary,
+        matrix,
+        guesses,
+        possible_answers,
+        progress,
+        false,
+    )
+}
+
+/// Computes the hints with the `matrix` if there is one, on the fly otherwise
+fn entropies_with_hints<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: Option<&HintMatrix>,
+    guesses: &[usize],
+    possible_answers: &[usize],
+    progress: &Progress,
+    keep_probabilities: bool,
+) -> Option<Vec<(usize, EntropiesData)>> {
+    match matrix {
+        Some(matrix) => {
+            let columns = possible_answers
+                .iter()
+                .map(|&answer| matrix.column(answer))
+                .collect::<Vec<_>>();
+            entropies_with(
+                dictionary,
+                guesses,
+                possible_answers,
+                progress,
+                keep_probabilities,
+                |guess| {
+                    let row = matrix.row(guess);
+                    let columns = &columns;
+                    move |k| row.get(columns[k])
+                },
+            )
+        }
+        None => {
+            let words_bytes = &dictionary.words_bytes;
+            entropies_with(
+                dictionary,
+                guesses,
+                possible_answers,
+                progress,
+                keep_probabilities,
+                |guess| {
+                    let guess_hints = GuessHints::new(&words_bytes[guess]);
+                    move |k| guess_hints.ind(&words_bytes[possible_answers[k]])
+                },
+            )
+        }
+    }
+}
+
+/// Hint indices of the longest supported words, 7 letters, are below 3^7
+pub(crate) const MAX_HINTS: usize = 2187;
+
+/// `n·log2(n)` for every bucket size `n` up to `len`
+fn n_log_n_table(len: usize) -> Vec<f64> {
+    (0..=len)
+        .map(|n| {
+            if n > 0 {
+                n as f64 * (n as f64).log2()
+            } else {
+                0.
+            }
+        })
+        .collect()
+}
+
+/// `hints_for` gives the function returning the hint index for the guess
+/// of each of the answers, by their position in `possible_answers`.
+/// The probabilities of the hints are only allocated with `keep_probabilities`.
+fn entropies_with<F, G, const N: usize>(
+    dictionary: &Dictionary<N>,
+    guesses: &[usize],
+    possible_answers: &[usize],
+    progress: &Progress,
+    keep_probabilities: bool,
+    hints_for: F,
+) -> Option<Vec<(usize, EntropiesData)>>
+where
+    F: Fn(usize) -> G + Sync,
+    G: Fn(usize) -> usize,
+{
+    progress.start(guesses.len());
+    let hints_len = dictionary.hints.len();
+    let answer_probabilities = {
+        let prob_norm: f64 = possible_answers
+            .iter()
+            .map(|&i| dictionary.probabilities[i])
+            .sum();
+        possible_answers
+            .iter()
+            .map(|&i| dictionary.probabilities[i] / prob_norm)
+            .collect::<Vec<_>>()
+    };
+    // with all the answers equally likely the entropy only depends on the bucket sizes,
+    // `H = log2(n) - Σ c·log2(c) / n`, so the buckets are counted instead of summed
+    let uniform = answer_probabilities
+        .windows(2)
+        .all(|pair| pair[0] == pair[1]);
+    let n_log_n = if uniform {
+        n_log_n_table(possible_answers.len())
+    } else {
+        vec![]
+    };
 
     #[cfg(feature = "parallel")]
     let guess_words_iter = {
         let min_len = if possible_answers.len() > 1000 {
             0
         } else {
-            guess_words_bytes.len()
+            guesses.len()
         };
-        guess_words_bytes.par_iter().with_min_len(min_len)
+        guesses.par_iter().with_min_len(min_len)
     };
 
     #[cfg(not(feature = "parallel"))]
-    let guess_words_iter = guess_words_bytes.iter();
+    let guess_words_iter = guesses.iter();
 
-    let entropies = guess_words_iter
-        .map(|guess_b| {
-            let mut guess_hints = vec![0.; dictionary.hints.len()];
-            for (correct, probability) in possible_answers
-                .iter()
-                .map(|&i| (&dictionary.words_bytes[i], &dictionary.probabilities[i]))
-            {
-                let hints = algo::get_hints(&guess_b, correct);
-                guess_hints[hints.to_ind()] += *probability / prob_norm;
+    guess_words_iter
+        .map(|&guess| {
+            if progress.is_cancelled() {
+                return None;
             }
 
-            let probs = Array1::<f64>::from_vec(guess_hints.clone());
-            let entropy = entropy(probs);
+            let hints_ind = hints_for(guess);
+            let mut counts = [0u32; MAX_HINTS];
+            let counts = &mut counts[..hints_len];
+            let mut guess_hints = [0f64; MAX_HINTS];
+            let guess_hints = &mut guess_hints[..hints_len];
 
-            EntropiesData::new(entropy, guess_hints)
-        })
-        .collect::<Vec<_>>();
+            let entropy = if uniform {
+                for k in 0..possible_answers.len() {
+                    counts[hints_ind(k)] += 1;
+                }
+
+                let n = possible_answers.len() as f64;
+                for (probability, &count) in guess_hints.iter_mut().zip(&*counts) {
+                    *probability = count as f64 / n;
+                }
+                if possible_answers.is_empty() {
+                    0.
+                } else {
+                    n.log2() - counts.iter().map(|&c| n_log_n[c as usize]).sum::<f64>() / n
+                }
+            } else {
+                for (k, &probability) in answer_probabilities.iter().enumerate() {
+                    let hints = hints_ind(k);
+                    guess_hints[hints] += probability;
+                    counts[hints] += 1;
+                }
+
+                -guess_hints
+                    .iter()
+                    .filter(|&&p| p > 0.)
+                    .map(|&p| p * p.log2())
+                    .sum::<f64>()
+            };
+
+            let mut entropies_data = EntropiesData::summary::<N>(entropy, guess_hints, counts);
+            if keep_probabilities {
+                entropies_data.probabilities = guess_hints.to_vec();
+            }
 
-    entropies
+            progress.advance();
+            Some((guess, entropies_data))
+        })
+        .collect::<Option<Vec<_>>>()
 }
 
 pub fn entropies_scored<const N: usize>(
     dictionary: &Dictionary<N>,
     answers: &[usize],
-    entropies: Vec<EntropiesData<N>>,
+    entropies: Vec<(usize, EntropiesData)>,
     uncertainty: Option<f64>,
-    calibration: Option<Calibration>,
-) -> Vec<(usize, EntropiesData<N>, f64)> {
+    model: Option<&dyn TurnsModel>,
+) -> Vec<(usize, EntropiesData, f64)> {
     let uncertainty = match uncertainty {
         Some(uncertainty) => uncertainty,
-        None => (dictionary.words.len() as f64).log2(),
+        None => (dictionary.answers.len() as f64).log2(),
     };
     let prob_norm: f64 = answers.iter().map(|&i| dictionary.probabilities[i]).sum();
+    let default_model = Model::default();
+    let model = model.unwrap_or(&default_model);
 
     let mut scores = entropies
         .into_iter()
-        .enumerate()
         .map(|(i, entropies_data)| {
             let prob = if answers.contains(&i) {
                 dictionary.probabilities[i] / prob_norm
@@ -94,12 +323,10 @@ pub fn entropies_scored<const N: usize>(
             };
 
             // the less the better
-            // we add 1 to bounded_log_c because we assume the guess is not correct
+            // we add 1 to bounded_turns because we assume the guess is not correct
             // so we must take at least one more turn
-            let calibration = calibration.unwrap_or_default();
             let left_diff = prob
-                + (1. + bounded_log_c(uncertainty - entropies_data.entropy, calibration))
-                    * (1. - prob);
+                + (1. + bounded_turns(uncertainty - entropies_data.entropy, model)) * (1. - prob);
 
             (i, entropies_data, left_diff)
         })
@@ -109,3 +336,215 @@ pub fn entropies_scored<const N: usize>(
 
     scores
 }
+
+/// Scores the guesses can be ranked by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScoreKind {
+    Entropy,
+    #[default]
+    ExpectedTurns,
+    MaxBucket,
+    ExpectedSize,
+    Buckets,
+    SolveProbability,
+}
+
+impl ScoreKind {
+    pub const ALL: [ScoreKind; 6] = [
+        ScoreKind::Entropy,
+        ScoreKind::ExpectedTurns,
+        ScoreKind::MaxBucket,
+        ScoreKind::ExpectedSize,
+        ScoreKind::Buckets,
+        ScoreKind::SolveProbability,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScoreKind::Entropy => "entropy",
+            ScoreKind::ExpectedTurns => "turns",
+            ScoreKind::MaxBucket => "max-bucket",
+            ScoreKind::ExpectedSize => "expected-size",
+            ScoreKind::Buckets => "buckets",
+            ScoreKind::SolveProbability => "solve-probability",
+        }
+    }
+
+    /// Value of the score for a guess with `turns` expected turns
+    pub fn value(self, entropies_data: &EntropiesData, turns: f64) -> f64 {
+        match self {
+            ScoreKind::Entropy => entropies_data.entropy,
+            ScoreKind::ExpectedTurns => turns,
+            ScoreKind::MaxBucket => entropies_data.max_bucket as f64,
+            ScoreKind::ExpectedSize => entropies_data.expected_size,
+            ScoreKind::Buckets => entropies_data.buckets as f64,
+            ScoreKind::SolveProbability => entropies_data.solve_probability,
+        }
+    }
+
+    pub fn higher_is_better(self) -> bool {
+        matches!(
+            self,
+            ScoreKind::Entropy | ScoreKind::Buckets | ScoreKind::SolveProbability
+        )
+    }
+
+    /// Orders the better of the guesses first
+    pub fn compare(
+        self,
+        (entropies_data1, turns1): (&EntropiesData, f64),
+        (entropies_data2, turns2): (&EntropiesData, f64),
+    ) -> Ordering {
+        let ordering = self
+            .value(entropies_data1, turns1)
+            .total_cmp(&self.value(entropies_data2, turns2));
+
+        if self.higher_is_better() {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl fmt::Display for ScoreKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown score \"{0}\", expected one of: entropy, turns, max-bucket, expected-size, buckets, solve-probability")]
+pub struct ParseScoreError(String);
+
+impl FromStr for ScoreKind {
+    type Err = ParseScoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ScoreKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| ParseScoreError(s.to_string()))
+    }
+}
+
+/// Sorts the scored guesses returned by [`entropies_scored`] by the `kind` score, the best first
+pub fn rank_scores(scores: &mut [(usize, EntropiesData, f64)], kind: ScoreKind) {
+    scores.sort_by(
+        |(_, entropies_data1, turns1), (_, entropies_data2, turns2)| {
+            kind.compare((entropies_data1, *turns1), (entropies_data2, *turns2))
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::WordN;
+    use crate::test_util::{abc_dictionary, abc_dictionary_with_answers};
+
+    #[test]
+    fn bucket_scores() {
+        let dictionary = abc_dictionary_with_answers(vec![0, 1, 2, 3]);
+        let answers = [0, 1, 2, 3];
+
+        let entropies = calculate_entropies(&dictionary, &[0, 4], &answers);
+
+        // "abcd" only tells "abcd" apart from the rest
+        let abcd = &entropies[0].1;
+        assert_eq!(3, abcd.max_bucket);
+        assert_eq!(2, abcd.buckets);
+        assert!((abcd.expected_size - 2.5).abs() < 1e-9);
+        assert!((abcd.solve_probability - 0.25).abs() < 1e-9);
+
+        let xefg = &entropies[1].1;
+        assert_eq!(1, xefg.max_bucket);
+        assert_eq!(4, xefg.buckets);
+        assert!((xefg.expected_size - 1.).abs() < 1e-9);
+        assert_eq!(0., xefg.solve_probability);
+
+        let mut scores = entropies_scored(&dictionary, &answers, entropies, None, None);
+        for kind in [ScoreKind::Entropy, ScoreKind::MaxBucket, ScoreKind::Buckets] {
+            rank_scores(&mut scores, kind);
+            assert_eq!(4, scores[0].0);
+        }
+        rank_scores(&mut scores, ScoreKind::SolveProbability);
+        assert_eq!(0, scores[0].0);
+    }
+
+    #[test]
+    fn progress_and_cancellation() {
+        let dictionary = abc_dictionary();
+        let guesses = [0, 1, 2, 3, 4, 5];
+
+        let progress = Progress::new();
+        let entropies = calculate_entropies_with_progress(
+            &dictionary,
+            &guesses,
+            &dictionary.answers,
+            &progress,
+        );
+        assert_eq!(Some(6), entropies.map(|entropies| entropies.len()));
+        assert_eq!((6, 6), (progress.done(), progress.total()));
+
+        progress.cancel();
+        let entropies = calculate_entropies_with_progress(
+            &dictionary,
+            &guesses,
+            &dictionary.answers,
+            &progress,
+        );
+        assert!(entropies.is_none());
+    }
+
+    #[test]
+    fn uncertainty_with_zero_probabilities() {
+        let mut dictionary = abc_dictionary_with_answers(vec![0, 1, 2, 3]);
+        dictionary.probabilities = vec![1., 1., 0., 0., 1., 1.];
+
+        assert!((uncertainty(&dictionary, &[0, 1, 2, 3]) - 1.).abs() < 1e-9);
+        assert_eq!(0., uncertainty(&dictionary, &[2, 3]));
+    }
+
+    #[test]
+    fn uniform_counts_entropy() {
+        let words = ["abcd", "abce", "bacd", "dcba", "xefg", "abdc"]
+            .into_iter()
+            .map(|w| WordN::try_from(w).unwrap())
+            .collect::<Vec<WordN<char, 4>>>();
+        let uniform = Dictionary::new(words.clone(), vec![1.; 6]);
+        // the same probabilities, but not exactly equal, so that the weighted path is taken
+        let weighted = Dictionary::new(words, vec![1., 1., 1., 1., 1., 1. + 1e-12]);
+        let guesses = (0..6).collect::<Vec<_>>();
+
+        let matrix = HintMatrix::new(&uniform);
+
+        let counted =
+            calculate_entropies_with_matrix(&uniform, &matrix, &guesses, &uniform.answers);
+        let summed =
+            calculate_entropies_with_matrix(&weighted, &matrix, &guesses, &weighted.answers);
+        for ((_, counted), (_, summed)) in counted.iter().zip(&summed) {
+            assert!((counted.entropy - summed.entropy).abs() < 1e-9);
+            assert!(
+                (counted.entropy - entropy(Array1::from(counted.probabilities.clone()))).abs()
+                    < 1e-9
+            );
+            assert_eq!(counted.max_bucket, summed.max_bucket);
+        }
+
+        // the hints computed on the fly are counted the same way
+        let on_the_fly = calculate_entropies(&uniform, &guesses, &uniform.answers);
+        assert_eq!(counted, on_the_fly);
+        for matrix in [None, Some(&matrix)] {
+            let summaries = guess_summaries(&uniform, matrix, &guesses, &uniform.answers);
+            for ((_, counted), (_, summary)) in counted.iter().zip(summaries) {
+                assert!(summary.probabilities.is_empty());
+                let without_probabilities = EntropiesData {
+                    probabilities: vec![],
+                    ..counted.clone()
+                };
+                assert_eq!(without_probabilities, summary);
+            }
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::structs::{
+    knowledge::{KnowledgeN, PartialChar},
+    Dictionary, WordN,
+};
+use crate::translator::Translator;
+
+/// Set of the translated letters, one bit for each possible byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LetterSet([u64; 4]);
+
+impl LetterSet {
+    const EMPTY: Self = Self([0; 4]);
+    const ALL: Self = Self([u64::MAX; 4]);
+
+    fn insert(&mut self, letter: u8) {
+        self.0[letter as usize >> 6] |= 1 << (letter & 63);
+    }
+
+    fn remove(&mut self, letter: u8) {
+        self.0[letter as usize >> 6] &= !(1 << (letter & 63));
+    }
+
+    fn contains(&self, letter: u8) -> bool {
+        self.0[letter as usize >> 6] >> (letter & 63) & 1 == 1
+    }
+}
+
+/// Bounds on the number of occurrences of a letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LetterCount {
+    letter: u8,
+    min: u8,
+    max: u8,
+}
+
+/// [`KnowledgeN`] compiled to the byte alphabet of a [`Translator`], checking the words
+/// with bit masks and plain counts instead of hash lookups, without allocating anything.
+/// Keeps exactly the same words as [`crate::algo::check`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnowledgeFilter<const N: usize> {
+    /// Letters allowed at each position
+    allowed: [LetterSet; N],
+    /// Letters with a known minimum or maximum number of occurrences
+    counts: Vec<LetterCount>,
+    /// Knowledge requires a letter missing from the alphabet, no word can match
+    impossible: bool,
+}
+
+impl<const N: usize> KnowledgeFilter<N> {
+    pub fn new(translator: &Translator, knowledge: &KnowledgeN<N>) -> Self {
+        let mut impossible = false;
+
+        let mut excluded_everywhere = LetterSet::EMPTY;
+        for &c in &knowledge.ruled_out {
+            if let Some(letter) = translator.to_byte(c) {
+                excluded_everywhere.insert(letter);
+            }
+        }
+
+        let mut allowed = [LetterSet::ALL; N];
+        for (allowed, placed) in allowed.iter_mut().zip(&knowledge.placed.word) {
+            match placed {
+                PartialChar::Some(c) => {
+                    *allowed = LetterSet::EMPTY;
+                    match translator.to_byte(*c) {
+                        Some(letter) => allowed.insert(letter),
+                        None => impossible = true,
+                    }
+                }
+                PartialChar::Excluded(excluded) => {
+                    for i in 0..4 {
+                        allowed.0[i] &= !excluded_everywhere.0[i];
+                    }
+                    for letter in excluded.iter().filter_map(|&c| translator.to_byte(c)) {
+                        allowed.remove(letter);
+                    }
+                }
+                PartialChar::None => {
+                    for i in 0..4 {
+                        allowed.0[i] &= !excluded_everywhere.0[i];
+                    }
+                }
+            }
+        }
+
+        let mut counts = vec![];
+        let letters = knowledge.known.keys().chain(
+            knowledge
+                .max_counts
+                .keys()
+                .filter(|c| !knowledge.known.contains_key(c)),
+        );
+        for &c in letters {
+            let min = knowledge.known.get(&c).copied().unwrap_or(0);
+            let max = knowledge.max_counts.get(&c).copied().unwrap_or(N as u8);
+            match translator.to_byte(c) {
+                Some(letter) => counts.push(LetterCount { letter, min, max }),
+                None if min > 0 => impossible = true,
+                None => (),
+            }
+        }
+
+        Self {
+            allowed,
+            counts,
+            impossible,
+        }
+    }
+
+    pub fn matches(&self, word: &WordN<u8, N>) -> bool {
+        if self.impossible {
+            return false;
+        }
+
+        let placed_ok = word
+            .0
+            .iter()
+            .zip(&self.allowed)
+            .fold(true, |ok, (&letter, allowed)| ok & allowed.contains(letter));
+
+        placed_ok
+            && self.counts.iter().all(|count| {
+                let occurrences = word
+                    .0
+                    .iter()
+                    .filter(|&&letter| letter == count.letter)
+                    .count();
+                (count.min as usize..=count.max as usize).contains(&occurrences)
+            })
+    }
+
+    /// Keeps only the `answers` (indices into `words_bytes`) matching the knowledge, in place
+    pub fn retain(&self, words_bytes: &[WordN<u8, N>], answers: &mut Vec<usize>) {
+        answers.retain(|&i| self.matches(&words_bytes[i]));
+    }
+}
+
+/// Same as [`crate::algo::get_answers`], using a [`KnowledgeFilter`] on the translated words
+pub fn get_answers<const N: usize>(
+    dictionary: &Dictionary<N>,
+    answers: &[usize],
+    knowledge: &KnowledgeN<N>,
+) -> Vec<usize> {
+    let filter = KnowledgeFilter::new(&dictionary.translator, knowledge);
+    answers
+        .iter()
+        .copied()
+        .filter(|&i| filter.matches(&dictionary.words_bytes[i]))
+        .collect()
+}
+
+/// Returns the indices of the dictionary words that can be played as the next guess.
+/// In hard mode only the words consistent with the `knowledge` gathered so far are allowed.
+pub fn get_guesses<const N: usize>(
+    dictionary: &Dictionary<N>,
+    knowledge: &KnowledgeN<N>,
+    hard_mode: bool,
+) -> Vec<usize> {
+    let guesses = 0..dictionary.words.len();
+    if !hard_mode {
+        return guesses.collect();
+    }
+
+    let filter = KnowledgeFilter::new(&dictionary.translator, knowledge);
+    guesses
+        .filter(|&i| filter.matches(&dictionary.words_bytes[i]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::{self, get_hints_and_update};
+    use proptest::prelude::*;
+
+    type Word = WordN<char, 5>;
+
+    fn word_strategy() -> impl Strategy<Value = Word> {
+        // a small alphabet, so that repeated letters are common
+        "[abcef]{5}".prop_map(|w| w.as_str().try_into().unwrap())
+    }
+
+    proptest! {
+        #[test]
+        fn same_answers_as_check(
+            correct in word_strategy(),
+            guesses in prop::collection::vec(word_strategy(), 0..4),
+            words in prop::collection::vec("[abce]{5}", 1..40),
+        ) {
+            // "f" is missing from the dictionary, but it can still be guessed
+            let words = words
+                .iter()
+                .map(|w| w.as_str().try_into().unwrap())
+                .collect::<Vec<Word>>();
+            let probabilities = vec![1.; words.len()];
+            let dictionary = Dictionary::new(words, probabilities);
+
+            let mut knowledge = KnowledgeN::none();
+            for guess in guesses {
+                knowledge = get_hints_and_update(&guess, &correct, knowledge).1;
+            }
+
+            let answers = (0..dictionary.words.len()).collect::<Vec<_>>();
+            let expected = algo::get_answers(&dictionary.words, &answers, &knowledge);
+            prop_assert_eq!(&expected, &get_answers(&dictionary, &answers, &knowledge));
+
+            let mut retained = answers;
+            KnowledgeFilter::new(&dictionary.translator, &knowledge)
+                .retain(&dictionary.words_bytes, &mut retained);
+            prop_assert_eq!(&expected, &retained);
+            prop_assert_eq!(expected, get_guesses(&dictionary, &knowledge, true));
+        }
+    }
+
+    #[test]
+    fn hard_mode_guesses() {
+        let words: Vec<Word> = ["crane", "slate", "trace", "caret"]
+            .into_iter()
+            .map(|w| w.try_into().unwrap())
+            .collect();
+        let (_, knowledge) = get_hints_and_update(&words[0], &words[2], KnowledgeN::none());
+        let dictionary = Dictionary::new(words, vec![1.; 4]);
+
+        assert_eq!(
+            vec![0, 1, 2, 3],
+            get_guesses(&dictionary, &knowledge, false)
+        );
+        assert_eq!(vec![2], get_guesses(&dictionary, &knowledge, true));
+    }
+}
@@ -1,9 +1,20 @@
+pub mod adversarial;
 pub mod algo;
+pub mod benchmark;
 pub mod calibration;
 pub mod data;
 pub mod entropy;
+pub mod filter;
+pub mod lookahead;
+pub mod multi_board;
+pub mod optimal;
+pub mod priors;
+pub mod progress;
 pub mod solvers;
+pub mod strategy;
 pub mod structs;
+#[cfg(test)]
+mod test_util;
 mod translator;
 mod util;
 pub use fxhash::FxHashMap;
@@ -0,0 +1,374 @@
+use std::borrow::Cow;
+
+use ndarray::Array;
+use rand::prelude::IteratorRandom;
+
+use crate::{
+    algo::{filter_answers, get_hints, partition_answers},
+    calibration::{bounded_turns, Model},
+    entropy::{calculate_entropies_with_matrix, entropies_scored, guess_summaries, uncertainty},
+    progress::{Cancelled, Progress},
+    solvers::solve,
+    strategy::{ExpectedTurns, Strategy, Turn},
+    structs::{hints::HintsN, word::WordN, Dictionary, HintMatrix},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LookaheadOptions {
+    /// Number of the best scored guesses expanded at every level
+    pub top_k: usize,
+    /// Number of guesses searched exactly before falling back to the calibrated estimate
+    pub depth: usize,
+    pub calibration: Model,
+}
+
+impl Default for LookaheadOptions {
+    fn default() -> Self {
+        Self {
+            top_k: 10,
+            depth: 2,
+            calibration: Model::default(),
+        }
+    }
+}
+
+/// Expected number of turns needed to solve the puzzle when `guess` is played
+/// with the given `answers` left, counting the `guess` itself
+fn guess_expected_turns<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
+    guess: usize,
+    answers: &[usize],
+    depth: usize,
+    options: &LookaheadOptions,
+    progress: &Progress,
+) -> Result<f64, Cancelled> {
+    let correct_ind = HintsN::<N>::correct().to_ind();
+    let prob_norm: f64 = answers.iter().map(|&i| dictionary.probabilities[i]).sum();
+
+    let mut turns = 1.;
+    for (hints_ind, bucket) in partition_answers(matrix, guess, answers) {
+        if hints_ind == correct_ind {
+            continue;
+        }
+        let bucket_prob: f64 = bucket.iter().map(|&i| dictionary.probabilities[i]).sum();
+        turns += bucket_prob / prob_norm
+            * expected_turns(dictionary, matrix, &bucket, depth, options, progress)?;
+    }
+    Ok(turns)
+}
+
+/// Scores the `top_k` of the `guesses` with the highest greedy score, returning them
+/// sorted by the expected number of turns, the best first.
+/// At the top level the `progress` counts the guesses scored.
+fn score_candidates<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
+    guesses: &[usize],
+    answers: &[usize],
+    depth: usize,
+    options: &LookaheadOptions,
+    progress: &Progress,
+) -> Result<Vec<(usize, f64)>, Cancelled> {
+    progress.check()?;
+    let entropies = guess_summaries(dictionary, Some(matrix), guesses, answers);
+    let scored = entropies_scored(
+        dictionary,
+        answers,
+        entropies,
+        Some(uncertainty(dictionary, answers)),
+        Some(&options.calibration),
+    );
+
+    let top_k = options.top_k.max(1).min(scored.len());
+    let is_root = depth == options.depth.max(1);
+    if is_root {
+        progress.start(top_k);
+    }
+
+    let mut candidates = vec![];
+    for (guess, _, _) in scored.into_iter().take(top_k) {
+        let turns = guess_expected_turns(
+            dictionary,
+            matrix,
+            guess,
+            answers,
+            depth - 1,
+            options,
+            progress,
+        )?;
+        candidates.push((guess, turns));
+        if is_root {
+            progress.advance();
+        }
+    }
+
+    candidates.sort_by(|(_, turns1), (_, turns2)| turns1.total_cmp(turns2));
+    Ok(candidates)
+}
+
+/// Expected number of turns needed to solve the puzzle with the `answers` left.
+/// The first `depth` guesses are searched among the `top_k` candidates, the turns
+/// after that are estimated with the calibrated model. With the `depth` of the `options`
+/// the `progress` counts the first guesses scored.
+pub fn expected_turns<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
+    answers: &[usize],
+    depth: usize,
+    options: &LookaheadOptions,
+    progress: &Progress,
+) -> Result<f64, Cancelled> {
+    Ok(match answers.len() {
+        0 => 0.,
+        1 => 1.,
+        _ if depth == 0 => bounded_turns(uncertainty(dictionary, answers), &options.calibration),
+        _ => {
+            let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+            score_candidates(
+                dictionary, matrix, &guesses, answers, depth, options, progress,
+            )?
+            .first()
+            .map(|&(_, turns)| turns)
+            .unwrap_or_default()
+        }
+    })
+}
+
+/// Picks the one of the `guesses` minimizing the expected number of turns over the `answers`
+/// left, returns it along with that number. The guesses after it are searched among all
+/// the words. The `progress` counts the guesses scored.
+pub fn best_guess<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
+    guesses: &[usize],
+    answers: &[usize],
+    options: &LookaheadOptions,
+    progress: &Progress,
+) -> Result<Option<(usize, f64)>, Cancelled> {
+    let depth = options.depth.max(1);
+    Ok(match answers {
+        [] => None,
+        &[answer] => Some((answer, 1.)),
+        _ => score_candidates(
+            dictionary, matrix, guesses, answers, depth, options, progress,
+        )?
+        .first()
+        .copied(),
+    })
+}
+
+/// Solves the puzzle for the `correct` answer picking every guess with `best_guess`,
+/// the `progress` starts over with every guess
+pub fn solve_lookahead<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
+    correct: &WordN<char, N>,
+    options: &LookaheadOptions,
+    print: bool,
+    progress: &Progress,
+) -> Result<(Vec<WordN<char, N>>, Vec<HintsN<N>>), Cancelled> {
+    let words = &dictionary.words;
+    let all_guesses = (0..words.len()).collect::<Vec<_>>();
+    let mut answers = dictionary.answers.clone();
+    let mut guesses = vec![];
+    let mut all_hints = vec![];
+
+    while let Some((guess_ind, turns)) = best_guess(
+        dictionary,
+        matrix,
+        &all_guesses,
+        &answers,
+        options,
+        progress,
+    )? {
+        let guess = &words[guess_ind];
+        let hints = get_hints(guess, correct);
+        answers = filter_answers(matrix, &answers, guess_ind, hints.to_ind());
+
+        if print {
+            println!("next_guess : {guess}, expected turns: {turns}, hints: {hints}");
+            println!("possibilities: {}", answers.len());
+        }
+
+        guesses.push(guess.clone());
+        all_hints.push(hints.clone());
+
+        if hints == HintsN::<N>::correct() {
+            break;
+        }
+    }
+
+    Ok((guesses, all_hints))
+}
+
+/// Guess with the least expected number of turns, searched a few guesses ahead.
+/// Without the matrix of the turn it's built for every guess.
+pub struct Lookahead(pub LookaheadOptions);
+
+impl<const N: usize> Strategy<N> for Lookahead {
+    fn choose(&self, turn: &Turn<N>) -> usize {
+        self.choose_with_progress(turn, &Progress::new())
+            .expect("Search can't be cancelled without the progress")
+    }
+
+    /// The `progress` counts the rows of the hint matrix if the turn has none,
+    /// then the first guesses scored
+    fn choose_with_progress(
+        &self,
+        turn: &Turn<N>,
+        progress: &Progress,
+    ) -> Result<usize, Cancelled> {
+        let matrix = match turn.matrix {
+            Some(matrix) => Cow::Borrowed(matrix),
+            None => Cow::Owned(HintMatrix::with_progress(turn.dictionary, progress)?),
+        };
+        Ok(best_guess(
+            turn.dictionary,
+            &matrix,
+            &turn.guesses(),
+            turn.answers,
+            &self.0,
+            progress,
+        )?
+        .expect("No answers left")
+        .0)
+    }
+}
+
+/// Turns needed to find one of the answers by the greedy solver and by the lookahead
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TurnsComparison {
+    /// Index into `Dictionary::words`
+    pub answer: usize,
+    pub greedy: usize,
+    pub lookahead: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GreedyComparison {
+    pub games: Vec<TurnsComparison>,
+    pub greedy_mean: f64,
+    pub lookahead_mean: f64,
+}
+
+/// Solves `n` random answers with both the greedy `solve` and `solve_lookahead`,
+/// returns the turns each of them needed along with the means.
+/// The `progress` counts the games played, it's checked for cancellation between them.
+pub fn compare_with_greedy<const N: usize>(
+    dictionary: &Dictionary<N>,
+    n: usize,
+    options: &LookaheadOptions,
+    progress: &Progress,
+) -> Result<GreedyComparison, Cancelled> {
+    let words = &dictionary.words;
+    let answers = dictionary
+        .answers
+        .iter()
+        .copied()
+        .choose_multiple(&mut rand::thread_rng(), n);
+
+    let matrix = HintMatrix::with_progress(dictionary, progress)?;
+    let guesses = (0..words.len()).collect::<Vec<_>>();
+    let initial_entropies =
+        calculate_entropies_with_matrix(dictionary, &matrix, &guesses, &dictionary.answers);
+
+    progress.start(answers.len());
+    let mut games = vec![];
+    for answer in answers {
+        progress.check()?;
+        let correct = &words[answer];
+        let (greedy_guesses, _, _, _) = solve(
+            &ExpectedTurns(options.calibration.clone()),
+            &initial_entropies,
+            dictionary,
+            &matrix,
+            correct,
+            false,
+            false,
+        );
+        let (lookahead_guesses, _) = solve_lookahead(
+            dictionary,
+            &matrix,
+            correct,
+            options,
+            false,
+            &Progress::new(),
+        )
+        .expect("Game can't be cancelled without the progress");
+
+        games.push(TurnsComparison {
+            answer,
+            greedy: greedy_guesses.len(),
+            lookahead: lookahead_guesses.len(),
+        });
+        progress.advance();
+    }
+
+    let mean = |turns: fn(&TurnsComparison) -> usize| {
+        Array::from_iter(games.iter().map(|game| turns(game) as f64))
+            .mean()
+            .unwrap_or_default()
+    };
+
+    Ok(GreedyComparison {
+        greedy_mean: mean(|game| game.greedy),
+        lookahead_mean: mean(|game| game.lookahead),
+        games,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::abc_dictionary;
+
+    #[test]
+    fn lookahead_solves() {
+        let dictionary = abc_dictionary();
+        let options = LookaheadOptions::default();
+        let progress = Progress::new();
+
+        let matrix = HintMatrix::new(&dictionary);
+        let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+        let answers = [0, 1, 2, 3];
+        let (guess, turns) = best_guess(
+            &dictionary,
+            &matrix,
+            &guesses,
+            &answers,
+            &options,
+            &progress,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(4, guess);
+        assert!((turns - 2.).abs() < 1e-9);
+        assert_eq!(progress.total(), progress.done());
+
+        for correct in &dictionary.words {
+            let (guesses, hints) =
+                solve_lookahead(&dictionary, &matrix, correct, &options, false, &progress).unwrap();
+            assert_eq!(Some(correct), guesses.last());
+            assert_eq!(Some(&HintsN::correct()), hints.last());
+        }
+
+        let comparison = compare_with_greedy(&dictionary, 3, &options, &progress).unwrap();
+        assert_eq!(3, comparison.games.len());
+        assert!(comparison.lookahead_mean >= 1.);
+        assert_eq!((3, 3), (progress.done(), progress.total()));
+
+        progress.cancel();
+        assert_eq!(
+            Err(Cancelled),
+            best_guess(
+                &dictionary,
+                &matrix,
+                &guesses,
+                &answers,
+                &options,
+                &progress
+            )
+        );
+    }
+}
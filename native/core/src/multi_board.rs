@@ -0,0 +1,241 @@
+use std::cmp::Ordering::Equal;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    algo::{get_hints, update_knowledge},
+    entropy::guess_summaries_with_progress,
+    progress::{Cancelled, Progress},
+    structs::{hints::HintsN, knowledge::KnowledgeN, Dictionary},
+};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MultiBoardError {
+    #[error("Expected hints for {expected} boards, got {got}")]
+    HintsCount { expected: usize, got: usize },
+    /// The boards are numbered from 1, as they're shown to the user
+    #[error("None of the answers of board {0} gives these hints")]
+    NoAnswers(usize),
+}
+
+/// One of the boards solved at the same time, with its own answer
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Board<const N: usize> {
+    pub knowledge: KnowledgeN<N>,
+    /// Answers consistent with the hints received so far
+    pub answers: Vec<usize>,
+    pub solved: bool,
+}
+
+/// Game where every guess is played on all the boards at once, as in Dordle or Quordle
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultiBoard<const N: usize> {
+    pub boards: Vec<Board<N>>,
+    pub guesses: Vec<usize>,
+}
+
+impl<const N: usize> MultiBoard<N> {
+    pub fn new(dictionary: &Dictionary<N>, boards: usize) -> Self {
+        let board = Board {
+            knowledge: KnowledgeN::none(),
+            answers: dictionary.answers.clone(),
+            solved: false,
+        };
+
+        Self {
+            boards: vec![board; boards],
+            guesses: vec![],
+        }
+    }
+
+    /// Plays the `guess` with the `hints` received on each of the boards,
+    /// the hints of the already solved boards are ignored
+    pub fn add_guess(
+        &mut self,
+        dictionary: &Dictionary<N>,
+        guess: usize,
+        hints: &[HintsN<N>],
+    ) -> Result<(), MultiBoardError> {
+        if hints.len() != self.boards.len() {
+            return Err(MultiBoardError::HintsCount {
+                expected: self.boards.len(),
+                got: hints.len(),
+            });
+        }
+
+        let guess_word = &dictionary.words[guess];
+        let guess_bytes = &dictionary.words_bytes[guess];
+        let boards = self
+            .boards
+            .iter()
+            .zip(hints)
+            .enumerate()
+            .map(|(i, (board, hints))| {
+                if board.solved {
+                    return Ok(board.clone());
+                }
+
+                let answers = board
+                    .answers
+                    .iter()
+                    .copied()
+                    .filter(|&answer| {
+                        get_hints(guess_bytes, &dictionary.words_bytes[answer]) == *hints
+                    })
+                    .collect::<Vec<_>>();
+                if answers.is_empty() {
+                    return Err(MultiBoardError::NoAnswers(i + 1));
+                }
+
+                Ok(Board {
+                    knowledge: update_knowledge(guess_word, hints, board.knowledge.clone()),
+                    answers,
+                    solved: *hints == HintsN::correct(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.boards = boards;
+        self.guesses.push(guess);
+        Ok(())
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.boards.iter().all(|board| board.solved)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MultiBoardOptions {
+    /// Bits a guess is worth for each board it's expected to solve,
+    /// favours finishing the boards with few answers left
+    pub solve_bonus: f64,
+}
+
+impl Default for MultiBoardOptions {
+    fn default() -> Self {
+        Self { solve_bonus: 2. }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultiBoardScore {
+    pub guess: usize,
+    /// Sum of the entropies over the unsolved boards
+    pub entropy: f64,
+    /// Expected number of the boards solved by the guess
+    pub solve_probability: f64,
+    pub score: f64,
+}
+
+/// Scores every guess over the unsolved boards, the best first.
+/// The `progress` starts over with each of the boards.
+pub fn score_guesses<const N: usize>(
+    dictionary: &Dictionary<N>,
+    state: &MultiBoard<N>,
+    options: &MultiBoardOptions,
+    progress: &Progress,
+) -> Result<Vec<MultiBoardScore>, Cancelled> {
+    let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+    let mut scores = guesses
+        .iter()
+        .map(|&guess| MultiBoardScore {
+            guess,
+            entropy: 0.,
+            solve_probability: 0.,
+            score: 0.,
+        })
+        .collect::<Vec<_>>();
+
+    // one board at a time, so that only one set of the hint distributions is kept in memory
+    for board in state.boards.iter().filter(|board| !board.solved) {
+        let entropies =
+            guess_summaries_with_progress(dictionary, None, &guesses, &board.answers, progress)
+                .ok_or(Cancelled)?;
+        for (score, (_, entropies_data)) in scores.iter_mut().zip(entropies) {
+            score.entropy += entropies_data.entropy;
+            score.solve_probability += entropies_data.solve_probability;
+        }
+    }
+
+    for score in &mut scores {
+        score.score = score.entropy + options.solve_bonus * score.solve_probability;
+    }
+    scores.sort_by(|score1, score2| score2.score.partial_cmp(&score1.score).unwrap_or(Equal));
+
+    Ok(scores)
+}
+
+/// Solves the boards with the given `answers`, returns the guesses played
+pub fn solve_multi<const N: usize>(
+    dictionary: &Dictionary<N>,
+    answers: &[usize],
+    options: &MultiBoardOptions,
+) -> Vec<usize> {
+    let mut state = MultiBoard::new(dictionary, answers.len());
+
+    while !state.is_solved() {
+        let guess = score_guesses(dictionary, &state, options, &Progress::new())
+            .expect("Scoring can't be cancelled without the progress")[0]
+            .guess;
+        let hints = answers
+            .iter()
+            .map(|&answer| {
+                get_hints(
+                    &dictionary.words_bytes[guess],
+                    &dictionary.words_bytes[answer],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        state
+            .add_guess(dictionary, guess, &hints)
+            .expect("Hints of the actual answers are always consistent");
+    }
+
+    state.guesses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::abc_dictionary;
+    use std::str::FromStr;
+
+    #[test]
+    fn multi_board_solves() {
+        let dictionary = abc_dictionary();
+        let options = MultiBoardOptions::default();
+
+        let mut state = MultiBoard::new(&dictionary, 2);
+        assert_eq!(
+            Err(MultiBoardError::HintsCount {
+                expected: 2,
+                got: 1
+            }),
+            state.add_guess(&dictionary, 0, &[HintsN::correct()])
+        );
+        assert_eq!(
+            Err(MultiBoardError::NoAnswers(2)),
+            state.add_guess(
+                &dictionary,
+                0,
+                &[HintsN::correct(), HintsN::from_str("wwwc").unwrap()]
+            )
+        );
+        assert!(state.guesses.is_empty());
+
+        let guesses = solve_multi(&dictionary, &[1, 5], &options);
+        assert!(guesses.contains(&1));
+        assert!(guesses.contains(&5));
+        assert!(guesses.len() <= 4);
+
+        let progress = Progress::new();
+        progress.cancel();
+        assert_eq!(
+            Err(Cancelled),
+            score_guesses(&dictionary, &state, &options, &progress)
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use std::cmp::Ordering::Equal;
+use std::collections::BTreeMap;
+
+use fxhash::FxHashMap;
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    progress::{Cancelled, Progress},
+    structs::{hints::HintsN, Dictionary, HintMatrix},
+};
+
+/// Decision tree of the guesses to play. Children are keyed by the index of
+/// the hints received for the `guess`, the correct hints have no child.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StrategyTree {
+    pub guess: usize,
+    pub expected_turns: f64,
+    pub children: BTreeMap<usize, StrategyTree>,
+}
+
+impl StrategyTree {
+    fn leaf(guess: usize) -> Self {
+        Self {
+            guess,
+            expected_turns: 1.,
+            children: BTreeMap::new(),
+        }
+    }
+
+    pub fn child<const N: usize>(&self, hints: &HintsN<N>) -> Option<&StrategyTree> {
+        self.children.get(&hints.to_ind())
+    }
+
+    /// Follows the tree along the `steps` played so far, given as pairs of the
+    /// guess and its hints index. Returns `None` once a guess leaves the strategy.
+    pub fn follow(&self, steps: impl IntoIterator<Item = (usize, usize)>) -> Option<&StrategyTree> {
+        steps.into_iter().try_fold(self, |node, (guess, hints)| {
+            if node.guess == guess {
+                node.children.get(&hints)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The number of guesses needed in the worst case
+    pub fn depth(&self) -> usize {
+        1 + self
+            .children
+            .values()
+            .map(|child| child.depth())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OptimalOptions {
+    /// Every answer has to be found within this number of guesses
+    pub max_depth: usize,
+    /// Limits the guesses tried at every node to the ones with the highest entropy,
+    /// the tree is only exact without the limit
+    pub candidates: Option<usize>,
+}
+
+impl Default for OptimalOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 6,
+            candidates: None,
+        }
+    }
+}
+
+/// Largest number of the subtrees remembered by the search, it starts over once it's full
+const MEMO_MAX_ENTRIES: usize = 100_000;
+
+struct Search<'a, const N: usize> {
+    dictionary: &'a Dictionary<N>,
+    matrix: &'a HintMatrix,
+    options: OptimalOptions,
+    progress: &'a Progress,
+    /// Subtrees keyed by the hash of the answers left and the depth left.
+    /// Two sets of the answers with the same 64-bit hash are unlikely enough to be ignored.
+    memo: FxHashMap<(u64, usize), Option<StrategyTree>>,
+}
+
+/// Answers giving the same hints for a guess, along with the index of the hints
+/// and the probability of getting them
+type Bucket = (usize, Vec<usize>, f64);
+
+impl<'a, const N: usize> Search<'a, N> {
+    /// Probabilities of the `answers`, normalized over them.
+    /// Equal if none of them has a positive probability.
+    fn probabilities(&self, answers: &[usize]) -> Vec<f64> {
+        let probabilities = &self.dictionary.probabilities;
+        let prob_norm: f64 = answers.iter().map(|&i| probabilities[i]).sum();
+        if prob_norm > 0. {
+            answers
+                .iter()
+                .map(|&i| probabilities[i] / prob_norm)
+                .collect()
+        } else {
+            vec![1. / answers.len() as f64; answers.len()]
+        }
+    }
+
+    /// Lower bound of the expected number of turns needed for the `answers`.
+    /// Apart from the first guess being right at most as often as the most probable answer,
+    /// the hints reveal at most log2(number of hints) bits per guess, so the expected number
+    /// of guesses can't be lower than the entropy of the answers divided by that.
+    fn lower_bound(&self, answers: &[usize]) -> f64 {
+        if answers.len() == 1 {
+            return 1.;
+        }
+
+        let probabilities = self.probabilities(answers);
+        let max_probability = probabilities.iter().copied().fold(0., f64::max);
+        let entropy: f64 = probabilities
+            .iter()
+            .filter(|&&p| p > 0.)
+            .map(|&p| -p * p.log2())
+            .sum();
+        let max_information = (self.dictionary.hints.len() as f64).log2();
+
+        (2. - max_probability).max(entropy / max_information)
+    }
+
+    fn search(
+        &mut self,
+        answers: &[usize],
+        depth_left: usize,
+    ) -> Result<Option<StrategyTree>, Cancelled> {
+        self.progress.check()?;
+        match (answers, depth_left) {
+            (_, 0) | ([], _) => return Ok(None),
+            (&[answer], _) => return Ok(Some(StrategyTree::leaf(answer))),
+            (_, 1) => return Ok(None),
+            _ => (),
+        }
+
+        let key = (fxhash::hash64(answers), depth_left);
+        if let Some(tree) = self.memo.get(&key) {
+            return Ok(tree.clone());
+        }
+
+        let tree = self.search_guesses(answers, depth_left)?;
+        if self.memo.len() >= MEMO_MAX_ENTRIES {
+            self.memo.clear();
+        }
+        self.memo.insert(key, tree.clone());
+        Ok(tree)
+    }
+
+    /// Entropy of the hints the `guess` gives for the answers, by their `columns` in the matrix,
+    /// counted in the `buckets` of every hint. `None` if the guess splits nothing off,
+    /// as such a guess can't be a part of the optimal tree.
+    fn guess_entropy(
+        &self,
+        guess: usize,
+        columns: &[usize],
+        probabilities: &[f64],
+        buckets: &mut [(u32, f64)],
+    ) -> Option<f64> {
+        let row = self.matrix.row(guess);
+        buckets.fill((0, 0.));
+        for (&column, &probability) in columns.iter().zip(probabilities) {
+            let bucket = &mut buckets[row.get(column)];
+            bucket.0 += 1;
+            bucket.1 += probability;
+        }
+
+        let correct_ind = HintsN::<N>::correct().to_ind();
+        let splits_nothing = buckets
+            .iter()
+            .enumerate()
+            .any(|(hints, &(count, _))| hints != correct_ind && count as usize == columns.len());
+
+        (!splits_nothing).then(|| {
+            buckets
+                .iter()
+                .filter(|&&(_, p)| p > 0.)
+                .map(|&(_, p)| -p * p.log2())
+                .sum()
+        })
+    }
+
+    /// Buckets of the answers left after the `guess`, without the solved one
+    fn buckets(
+        &self,
+        guess: usize,
+        answers: &[usize],
+        columns: &[usize],
+        probabilities: &[f64],
+    ) -> Vec<Bucket> {
+        let row = self.matrix.row(guess);
+        let correct_ind = HintsN::<N>::correct().to_ind();
+        let mut hinted = answers
+            .iter()
+            .zip(columns)
+            .zip(probabilities)
+            .map(|((&answer, &column), &probability)| (row.get(column), answer, probability))
+            .filter(|&(hints, _, _)| hints != correct_ind)
+            .collect::<Vec<_>>();
+        hinted.sort_unstable_by_key(|&(hints, answer, _)| (hints, answer));
+
+        let mut buckets: Vec<Bucket> = vec![];
+        for (hints, answer, probability) in hinted {
+            match buckets.last_mut() {
+                Some((last_hints, bucket, bucket_prob)) if *last_hints == hints => {
+                    bucket.push(answer);
+                    *bucket_prob += probability;
+                }
+                _ => buckets.push((hints, vec![answer], probability)),
+            }
+        }
+        buckets
+    }
+
+    fn search_guesses(
+        &mut self,
+        answers: &[usize],
+        depth_left: usize,
+    ) -> Result<Option<StrategyTree>, Cancelled> {
+        let columns = answers
+            .iter()
+            .map(|&answer| self.matrix.column(answer))
+            .collect::<Vec<_>>();
+        let probabilities = self.probabilities(answers);
+        let hints_len = self.dictionary.hints.len();
+
+        let candidates = {
+            let this = &*self;
+            let entropy_of = |buckets: &mut Vec<(u32, f64)>, guess: usize| {
+                this.guess_entropy(guess, &columns, &probabilities, buckets)
+                    .map(|entropy| (guess, entropy))
+            };
+            let guesses = 0..this.dictionary.words.len();
+
+            #[cfg(feature = "parallel")]
+            let candidates = guesses
+                .into_par_iter()
+                .map_init(|| vec![(0, 0.); hints_len], entropy_of)
+                .flatten()
+                .collect::<Vec<_>>();
+
+            #[cfg(not(feature = "parallel"))]
+            let candidates = {
+                let mut buckets = vec![(0, 0.); hints_len];
+                guesses
+                    .filter_map(|guess| entropy_of(&mut buckets, guess))
+                    .collect::<Vec<_>>()
+            };
+
+            candidates
+        };
+
+        let mut candidates = candidates;
+        candidates.sort_by(|(_, entropy1), (_, entropy2)| {
+            entropy2.partial_cmp(entropy1).unwrap_or(Equal)
+        });
+        if let Some(limit) = self.options.candidates {
+            candidates.truncate(limit);
+        }
+
+        let is_root = depth_left == self.options.max_depth;
+        if is_root {
+            self.progress.start(candidates.len());
+        }
+
+        let mut best: Option<StrategyTree> = None;
+        for (guess, _) in candidates {
+            let buckets = self.buckets(guess, answers, &columns, &probabilities);
+            let best_turns = best
+                .as_ref()
+                .map(|tree| tree.expected_turns)
+                .unwrap_or(f64::INFINITY);
+
+            if let Some(tree) = self.guess_tree(guess, buckets, depth_left, best_turns)? {
+                best = Some(tree);
+            }
+            if is_root {
+                self.progress.advance();
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Tree starting with the `guess`, as long as it needs fewer turns than `best_turns`
+    fn guess_tree(
+        &mut self,
+        guess: usize,
+        mut buckets: Vec<Bucket>,
+        depth_left: usize,
+        best_turns: f64,
+    ) -> Result<Option<StrategyTree>, Cancelled> {
+        let bounds = buckets
+            .iter()
+            .map(|(_, bucket, p)| p * self.lower_bound(bucket))
+            .collect::<Vec<_>>();
+        let mut bounds_left: f64 = bounds.iter().sum();
+        if 1. + bounds_left >= best_turns {
+            return Ok(None);
+        }
+
+        // the most probable buckets first, to prune as early as possible
+        let mut order = (0..buckets.len()).collect::<Vec<_>>();
+        order.sort_by(|&i, &j| buckets[j].2.partial_cmp(&buckets[i].2).unwrap_or(Equal));
+
+        let mut expected_turns = 1.;
+        let mut children = BTreeMap::new();
+        for i in order {
+            let (hints_ind, bucket, p) = std::mem::take(&mut buckets[i]);
+            bounds_left -= bounds[i];
+
+            let child = match self.search(&bucket, depth_left - 1)? {
+                Some(child) => child,
+                None => return Ok(None),
+            };
+            expected_turns += p * child.expected_turns;
+            children.insert(hints_ind, child);
+
+            if expected_turns + bounds_left >= best_turns {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(StrategyTree {
+            guess,
+            expected_turns,
+            children,
+        }))
+    }
+}
+
+/// Computes the decision tree minimizing the expected number of guesses needed to find
+/// any of the dictionary answers, with every answer found within `options.max_depth` guesses.
+/// Returns `None` if no such tree exists. The `progress` counts the first guesses tried.
+pub fn optimal_tree<const N: usize>(
+    dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
+    options: &OptimalOptions,
+    progress: &Progress,
+) -> Result<Option<StrategyTree>, Cancelled> {
+    let mut search = Search {
+        dictionary,
+        matrix,
+        options: *options,
+        progress,
+        memo: FxHashMap::default(),
+    };
+
+    search.search(&dictionary.answers, options.max_depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::abc_dictionary_with_answers;
+
+    #[test]
+    fn optimal_strategy_tree() {
+        let dictionary = abc_dictionary_with_answers(vec![0, 1, 2, 3]);
+        let matrix = HintMatrix::new(&dictionary);
+        let progress = Progress::new();
+
+        let tree = optimal_tree(&dictionary, &matrix, &OptimalOptions::default(), &progress)
+            .unwrap()
+            .unwrap();
+        assert_eq!(4, tree.guess);
+        assert!((tree.expected_turns - 2.).abs() < 1e-9);
+        assert_eq!(2, tree.depth());
+
+        let hints = crate::algo::get_hints(&dictionary.words[4], &dictionary.words[3]);
+        let next = tree.follow([(4, hints.to_ind())]).unwrap();
+        assert_eq!(3, next.guess);
+        assert!(tree.follow([(0, hints.to_ind())]).is_none());
+
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(tree, serde_json::from_str(&json).unwrap());
+
+        let options = OptimalOptions {
+            max_depth: 1,
+            candidates: None,
+        };
+        assert_eq!(
+            Ok(None),
+            optimal_tree(&dictionary, &matrix, &options, &progress)
+        );
+
+        progress.cancel();
+        assert_eq!(
+            Err(Cancelled),
+            optimal_tree(&dictionary, &matrix, &OptimalOptions::default(), &progress)
+        );
+    }
+
+    #[test]
+    fn optimal_with_zero_probabilities() {
+        let mut dictionary = abc_dictionary_with_answers(vec![0, 1, 2, 3]);
+        dictionary.probabilities = vec![1., 0., 0., 0., 1., 1.];
+        let matrix = HintMatrix::new(&dictionary);
+
+        let tree = optimal_tree(
+            &dictionary,
+            &matrix,
+            &OptimalOptions::default(),
+            &Progress::new(),
+        )
+        .unwrap()
+        .unwrap();
+        assert!((tree.expected_turns - 1.).abs() < 1e-9);
+    }
+}
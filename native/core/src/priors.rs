@@ -0,0 +1,172 @@
+use std::cmp::Ordering::Equal;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::structs::Dictionary;
+
+/// Turns the word frequencies (raw counts or probabilities) into the prior probabilities
+/// of the words being the answer. Only the ratios matter, the probabilities get normalized
+/// over the possible answers anyway.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Prior {
+    /// Every word equally likely
+    Uniform,
+    /// Frequencies used as they are
+    #[default]
+    Raw,
+    /// Logarithm of the frequency relative to the rarest word,
+    /// flattens the extremely peaked corpus frequencies
+    Log,
+    /// Sigmoid over the frequency rank, the `midpoint` is the fraction of the most frequent
+    /// words with probabilities above 0.5 and `width` controls how steep the transition is
+    Sigmoid { midpoint: f64, width: f64 },
+    /// Decreasing linearly with the frequency rank
+    Rank,
+}
+
+impl Prior {
+    pub const ALL: [Prior; 5] = [
+        Prior::Uniform,
+        Prior::Raw,
+        Prior::Log,
+        Prior::Sigmoid {
+            midpoint: 0.25,
+            width: 10.,
+        },
+        Prior::Rank,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Prior::Uniform => "uniform",
+            Prior::Raw => "raw",
+            Prior::Log => "log",
+            Prior::Sigmoid { .. } => "sigmoid",
+            Prior::Rank => "rank",
+        }
+    }
+
+    pub fn apply(self, frequencies: &[f64]) -> Vec<f64> {
+        match self {
+            Prior::Uniform => vec![1.; frequencies.len()],
+            Prior::Raw => frequencies.to_vec(),
+            Prior::Log => {
+                let min = frequencies
+                    .iter()
+                    .copied()
+                    .filter(|&frequency| frequency > 0.)
+                    .fold(f64::INFINITY, f64::min);
+                frequencies
+                    .iter()
+                    .map(|&frequency| {
+                        if frequency > 0. {
+                            (frequency / min).ln() + 1.
+                        } else {
+                            0.
+                        }
+                    })
+                    .collect()
+            }
+            Prior::Sigmoid { midpoint, width } => {
+                let len = frequencies.len() as f64;
+                Self::by_rank(frequencies, |rank| {
+                    let x = width * (midpoint - rank as f64 / len);
+                    1. / (1. + (-x).exp())
+                })
+            }
+            Prior::Rank => {
+                let len = frequencies.len() as f64;
+                Self::by_rank(frequencies, |rank| (len - rank as f64) / len)
+            }
+        }
+    }
+
+    /// Probabilities as a function of the rank, 0 being the most frequent word
+    fn by_rank<F>(frequencies: &[f64], probability: F) -> Vec<f64>
+    where
+        F: Fn(usize) -> f64,
+    {
+        let mut order = (0..frequencies.len()).collect::<Vec<_>>();
+        order.sort_by(|&i, &j| frequencies[j].partial_cmp(&frequencies[i]).unwrap_or(Equal));
+
+        let mut probabilities = vec![0.; frequencies.len()];
+        for (rank, i) in order.into_iter().enumerate() {
+            probabilities[i] = probability(rank);
+        }
+        probabilities
+    }
+
+    /// Same dictionary with the prior applied to its probabilities
+    pub fn apply_to<const N: usize>(self, dictionary: &Dictionary<N>) -> Dictionary<N> {
+        Dictionary {
+            probabilities: self.apply(&dictionary.probabilities),
+            ..dictionary.clone()
+        }
+    }
+}
+
+impl fmt::Display for Prior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Prior::Sigmoid { midpoint, width } => write!(f, "sigmoid:{midpoint}:{width}"),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error(
+    "Unknown prior \"{0}\", expected one of: uniform, raw, log, sigmoid[:midpoint:width], rank"
+)]
+pub struct ParsePriorError(String);
+
+impl FromStr for Prior {
+    type Err = ParsePriorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParsePriorError(s.to_string());
+        match s.split_once(':') {
+            Some(("sigmoid", params)) => {
+                let (midpoint, width) = params.split_once(':').ok_or_else(err)?;
+                Ok(Prior::Sigmoid {
+                    midpoint: midpoint.parse().map_err(|_| err())?,
+                    width: width.parse().map_err(|_| err())?,
+                })
+            }
+            Some(_) => Err(err()),
+            None => Prior::ALL
+                .into_iter()
+                .find(|prior| prior.name() == s)
+                .ok_or_else(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Prior::Uniform)]
+    #[case(Prior::Raw)]
+    #[case(Prior::Log)]
+    #[case(Prior::Sigmoid { midpoint: 0.5, width: 4. })]
+    #[case(Prior::Rank)]
+    fn prior_keeps_order(#[case] prior: Prior) {
+        let frequencies = [1e-7, 0.3, 1e-3, 0.];
+        let probabilities = prior.apply(&frequencies);
+
+        assert_eq!(frequencies.len(), probabilities.len());
+        assert!(probabilities.iter().all(|&p| p >= 0.));
+        assert!(probabilities[1] >= probabilities[2]);
+        assert!(probabilities[2] >= probabilities[0]);
+        assert!(probabilities[0] > 0.);
+
+        assert_eq!(prior, prior.to_string().parse().unwrap());
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed};
+use thiserror::Error;
+
+type Callback = Box<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Shared between a long computation and whoever waits for it, tracks how many
+/// of the steps are done and lets the computation be cancelled from another thread
+#[derive(Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+    /// Called with the number of the steps done and the total after every step
+    callback: Option<Callback>,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_callback<F>(callback: F) -> Self
+    where
+        F: Fn(usize, usize) + Send + Sync + 'static,
+    {
+        Self {
+            callback: Some(Box::new(callback)),
+            ..Self::default()
+        }
+    }
+
+    /// Asks the computation to stop, it gives up at the next step
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Relaxed)
+    }
+
+    /// Fails once the computation was cancelled
+    pub(crate) fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn done(&self) -> usize {
+        self.done.load(Relaxed)
+    }
+
+    pub fn total(&self) -> usize {
+        self.total.load(Relaxed)
+    }
+
+    pub(crate) fn start(&self, total: usize) {
+        self.done.store(0, Relaxed);
+        self.total.store(total, Relaxed);
+    }
+
+    pub(crate) fn advance(&self) {
+        let done = self.done.fetch_add(1, Relaxed) + 1;
+        if let Some(callback) = &self.callback {
+            callback(done, self.total());
+        }
+    }
+}
+
+/// Returned by the computations which were cancelled through their [`Progress`]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("Calculation was cancelled")]
+pub struct Cancelled;
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("done", &self.done())
+            .field("total", &self.total())
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
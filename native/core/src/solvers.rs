@@ -1,16 +1,24 @@
-use std::{cmp::Ordering::Equal, time::Instant};
+use std::time::Instant;
 
 use nalgebra::Scalar;
 use ndarray::Array;
 use num::One;
 use num_traits::Float;
 use rand::prelude::IteratorRandom;
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 use crate::{
-    algo::{get_answers, get_hints_and_update},
-    calibration::{bounded_log_c, Calibration},
-    entropy::calculate_entropies,
-    structs::{hints::HintsN, knowledge::KnowledgeN, word::WordN, Dictionary, EntropiesData},
+    algo::{filter_answers, get_hints_and_update},
+    calibration::{bounded_log_c, Calibration, Model},
+    entropy::{
+        self, calculate_entropies_with_matrix, calculate_entropies_with_matrix_and_progress,
+    },
+    progress::{Cancelled, Progress},
+    strategy::{ExpectedTurns, Strategy, Turn},
+    structs::{
+        hints::HintsN, knowledge::KnowledgeN, word::WordN, Dictionary, EntropiesData, HintMatrix,
+    },
     util::print_vec,
 };
 
@@ -27,91 +35,62 @@ pub fn expected_turns(x: f64, calibration: Calibration) -> f64 {
     bounded_log_c(x, calibration).clamp(f64::NEG_INFINITY, 1.)
 }
 
+/// Solves the puzzle for the `correct` answer with the guesses chosen by `strategy`.
+/// With `hard_mode` set every guess has to be consistent with the hints revealed so far.
 pub fn solve<const N: usize>(
-    initial_entropies: &Vec<EntropiesData<N>>,
+    strategy: &dyn Strategy<N>,
+    initial_entropies: &[(usize, EntropiesData)],
     dictionary: &Dictionary<N>,
+    matrix: &HintMatrix,
     correct: &WordN<char, N>,
+    hard_mode: bool,
     print: bool,
 ) -> (Vec<WordN<char, N>>, Vec<HintsN<N>>, Vec<f64>, Vec<f64>) {
-    let words = &dictionary.words;
-    let mut answers = (0..words.len()).collect::<Vec<_>>();
+    let mut answers = dictionary.answers.clone();
     let mut knowledge = KnowledgeN::<N>::default();
     let mut total_information = Vec::<f64>::new();
     let mut uncertainties = Vec::<f64>::new();
-    let mut guesses = vec![];
-    let mut all_hints = vec![];
-    let full_information = (words.len() as f64).log2();
+    let mut history = vec![];
+    let full_information = (answers.len() as f64).log2();
     let mut uncertainty = full_information;
-    let mut prob_norm: f64 = answers.iter().map(|&i| dictionary.probabilities[i]).sum();
+    let mut prob_norm: f64;
 
     for i in 0.. {
         uncertainties.push(uncertainty);
         if answers.len() == 1 {
             total_information.push(total_information.last().copied().unwrap_or_default());
-            guesses.push(dictionary.words[*answers.first().unwrap()].clone());
-            all_hints.push(HintsN::<N>::correct());
+            history.push((
+                dictionary.words[*answers.first().unwrap()].clone(),
+                HintsN::<N>::correct(),
+            ));
             break;
         }
-        let entropies = if i == 0 {
-            initial_entropies.clone()
-        } else {
-            calculate_entropies(dictionary, &answers)
-        };
-
-        let mut scores = entropies
-            .into_iter()
-            .enumerate()
-            .map(|(i, entropies_data)| {
-                let prob = if answers.contains(&i) {
-                    dictionary.probabilities[i] / prob_norm
-                } else {
-                    0.
-                };
-
-                // the less the better
-                let left_diff =
-                    expected_turns(uncertainty - entropies_data.entropy, Calibration::default())
-                        * (1. - prob);
-
-                (i, entropies_data, left_diff)
-            })
-            .collect::<Vec<_>>();
-
-        scores.sort_by(|&(_, _, score1), &(_, _, score2)| {
-            score1.partial_cmp(&score2).unwrap_or(Equal)
-        });
-
-        if print {
-            println!("Prob norm: {prob_norm}");
-            println!("10 best gueses:");
-            for &(i, _, score) in scores.iter().take(10) {
-                println!("{}: {score}", &dictionary.words[i]);
-            }
-        }
 
-        let guess = &dictionary.words[scores.into_iter().next().unwrap().0];
+        let turn = Turn {
+            dictionary,
+            matrix: Some(matrix),
+            answers: &answers,
+            history: &history,
+            hard_mode,
+            entropies: (i == 0).then_some(initial_entropies),
+        };
+        let guess_ind = strategy.choose(&turn);
+        let guess = &dictionary.words[guess_ind];
 
         let (hints, knowledge_new) = get_hints_and_update(guess, correct, knowledge);
 
         knowledge = knowledge_new;
-        answers = get_answers(words.clone(), &knowledge);
+        answers = filter_answers(matrix, &answers, guess_ind, hints.to_ind());
 
         prob_norm = answers.iter().map(|&i| dictionary.probabilities[i]).sum();
 
-        uncertainty = answers
-            .iter()
-            .map(|&i| {
-                let probability = dictionary.probabilities[i] / prob_norm;
-                -probability * probability.log2()
-            })
-            .sum();
+        uncertainty = entropy::uncertainty(dictionary, &answers);
 
         total_information.push(full_information - uncertainty);
 
         let last_total_information = total_information.last().copied().unwrap_or_default();
 
-        guesses.push(guess.clone());
-        all_hints.push(hints.clone());
+        history.push((guess.clone(), hints.clone()));
 
         if print {
             println!("next_guess : {guess}, hints: {hints}");
@@ -133,16 +112,80 @@ pub fn solve<const N: usize>(
         }
     }
 
+    let (guesses, all_hints) = history.into_iter().unzip();
     (guesses, all_hints, total_information, uncertainties)
 }
 
+/// Solves every one of the `answers` (indices into `dictionary.words`) without printing
+/// anything, returns the uncertainty before each of the guesses along with the number
+/// of the turns left, counting that guess. The `progress` counts the rows of the hint matrix,
+/// then the initial entropies and then the games played.
+pub fn uncertainty_data<const N: usize>(
+    dictionary: &Dictionary<N>,
+    answers: &[usize],
+    progress: &Progress,
+) -> Result<Vec<(f64, usize)>, Cancelled> {
+    let matrix = HintMatrix::with_progress(dictionary, progress)?;
+    let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+    let initial_entropies = calculate_entropies_with_matrix_and_progress(
+        dictionary,
+        &matrix,
+        &guesses,
+        &dictionary.answers,
+        progress,
+    )
+    .ok_or(Cancelled)?;
+    let strategy = ExpectedTurns(Model::default());
+
+    progress.start(answers.len());
+    let play = |&answer: &usize| {
+        progress.check()?;
+        let (guesses, _, _, uncertainties) = solve(
+            &strategy,
+            &initial_entropies,
+            dictionary,
+            &matrix,
+            &dictionary.words[answer],
+            false,
+            false,
+        );
+        progress.advance();
+
+        Ok(uncertainties
+            .into_iter()
+            .enumerate()
+            .map(|(i, uncertainty)| (uncertainty, guesses.len() - i))
+            .collect::<Vec<_>>())
+    };
+
+    #[cfg(feature = "parallel")]
+    let games = answers
+        .par_iter()
+        .map(play)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    #[cfg(not(feature = "parallel"))]
+    let games = answers.iter().map(play).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(games.concat())
+}
+
 pub fn solve_random<const N: usize>(dictionary: &Dictionary<N>, n: usize) -> Vec<(f64, i32)> {
     let words = &dictionary.words;
-    let answers = (0..words.len()).collect::<Vec<_>>();
-    let correct_words = words.iter().choose_multiple(&mut rand::thread_rng(), n);
+    let answers = &dictionary.answers;
+    let correct_words = answers
+        .iter()
+        .map(|&i| &words[i])
+        .choose_multiple(&mut rand::thread_rng(), n);
+
+    let start = Instant::now();
+    let matrix = HintMatrix::new(dictionary);
+    let duration = start.elapsed();
+    println!("Hint matrix calculation took: {}ms", duration.as_millis());
 
     let start = Instant::now();
-    let initial_entropies = calculate_entropies(dictionary, &answers);
+    let guesses = (0..words.len()).collect::<Vec<_>>();
+    let initial_entropies = calculate_entropies_with_matrix(dictionary, &matrix, &guesses, answers);
     let duration = start.elapsed();
     println!(
         "Initial entropies calculation took: {}ms",
@@ -154,8 +197,15 @@ pub fn solve_random<const N: usize>(dictionary: &Dictionary<N>, n: usize) -> Vec
 
     for correct in correct_words {
         println!("correct: {correct}");
-        let (guesses, hints, entropies, uncertainties) =
-            solve(&initial_entropies, dictionary, correct, false);
+        let (guesses, hints, entropies, uncertainties) = solve(
+            &ExpectedTurns(Model::default()),
+            &initial_entropies,
+            dictionary,
+            &matrix,
+            correct,
+            false,
+            false,
+        );
 
         print_vec(&guesses);
         print_vec(&hints);
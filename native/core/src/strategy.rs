@@ -0,0 +1,271 @@
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use rand::prelude::IteratorRandom;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    adversarial::{AdversarialOptions, WorstCase},
+    algo::update_knowledge,
+    calibration::Model,
+    entropy::{entropies_scored, guess_summaries, uncertainty},
+    filter::get_guesses,
+    lookahead::{Lookahead, LookaheadOptions},
+    progress::{Cancelled, Progress},
+    structs::{knowledge::KnowledgeN, Dictionary, EntropiesData, HintMatrix, HintsN, WordN},
+};
+
+/// State of the game the next guess is chosen in
+pub struct Turn<'a, const N: usize> {
+    pub dictionary: &'a Dictionary<N>,
+    /// Speeds up the entropy calculation if given
+    pub matrix: Option<&'a HintMatrix>,
+    /// Answers consistent with the `history`
+    pub answers: &'a [usize],
+    pub history: &'a [(WordN<char, N>, HintsN<N>)],
+    pub hard_mode: bool,
+    /// Entropies of the allowed guesses computed beforehand, e.g. for the first turn
+    pub entropies: Option<&'a [(usize, EntropiesData)]>,
+}
+
+impl<'a, const N: usize> Turn<'a, N> {
+    pub fn knowledge(&self) -> KnowledgeN<N> {
+        self.history
+            .iter()
+            .fold(KnowledgeN::none(), |knowledge, (guess, hints)| {
+                update_knowledge(guess, hints, knowledge)
+            })
+    }
+
+    /// Guesses allowed in this turn
+    pub fn guesses(&self) -> Vec<usize> {
+        get_guesses(self.dictionary, &self.knowledge(), self.hard_mode)
+    }
+
+    pub fn entropies(&self) -> Cow<'a, [(usize, EntropiesData)]> {
+        match (self.entropies, self.matrix) {
+            (Some(entropies), _) => Cow::Borrowed(entropies),
+            (None, matrix) => Cow::Owned(guess_summaries(
+                self.dictionary,
+                matrix,
+                &self.guesses(),
+                self.answers,
+            )),
+        }
+    }
+
+    pub fn uncertainty(&self) -> f64 {
+        uncertainty(self.dictionary, self.answers)
+    }
+
+    fn is_answer(&self, guess: usize) -> bool {
+        self.answers.contains(&guess)
+    }
+}
+
+pub trait Strategy<const N: usize>: Sync {
+    /// Index of the next guess into `turn.dictionary.words`
+    fn choose(&self, turn: &Turn<N>) -> usize;
+
+    /// Same as [`Strategy::choose`], stopping once the `progress` is cancelled.
+    /// Only the strategies searching ahead report to the `progress`.
+    fn choose_with_progress(
+        &self,
+        turn: &Turn<N>,
+        progress: &Progress,
+    ) -> Result<usize, Cancelled> {
+        progress.check()?;
+        Ok(self.choose(turn))
+    }
+}
+
+/// Guess revealing the most information, a possible answer on a tie
+pub struct MaxEntropy;
+
+impl<const N: usize> Strategy<N> for MaxEntropy {
+    fn choose(&self, turn: &Turn<N>) -> usize {
+        turn.entropies()
+            .iter()
+            .max_by(|(i, data1), (j, data2)| {
+                data1
+                    .entropy
+                    .total_cmp(&data2.entropy)
+                    .then(turn.is_answer(*i).cmp(&turn.is_answer(*j)))
+            })
+            .expect("No guesses available")
+            .0
+    }
+}
+
+/// Guess with the least expected number of turns, estimated from its entropy
+pub struct ExpectedTurns(pub Model);
+
+impl<const N: usize> Strategy<N> for ExpectedTurns {
+    fn choose(&self, turn: &Turn<N>) -> usize {
+        entropies_scored(
+            turn.dictionary,
+            turn.answers,
+            turn.entropies().into_owned(),
+            Some(turn.uncertainty()),
+            Some(&self.0),
+        )
+        .first()
+        .expect("No guesses available")
+        .0
+    }
+}
+
+/// Guess leaving the fewest answers in the worst case, a possible answer on a tie
+pub struct Minimax;
+
+impl<const N: usize> Strategy<N> for Minimax {
+    fn choose(&self, turn: &Turn<N>) -> usize {
+        turn.entropies()
+            .iter()
+            .min_by_key(|(i, data)| (data.max_bucket, !turn.is_answer(*i)))
+            .expect("No guesses available")
+            .0
+    }
+}
+
+/// The most probable of the answers left
+pub struct MostProbable;
+
+impl<const N: usize> Strategy<N> for MostProbable {
+    fn choose(&self, turn: &Turn<N>) -> usize {
+        let probabilities = &turn.dictionary.probabilities;
+        *turn
+            .answers
+            .iter()
+            .max_by(|&&i, &&j| probabilities[i].total_cmp(&probabilities[j]))
+            .expect("No answers left")
+    }
+}
+
+/// Random one of the answers left
+pub struct RandomAnswer;
+
+impl<const N: usize> Strategy<N> for RandomAnswer {
+    fn choose(&self, turn: &Turn<N>) -> usize {
+        *turn
+            .answers
+            .iter()
+            .choose(&mut rand::thread_rng())
+            .expect("No answers left")
+    }
+}
+
+/// The available strategies, selectable by name
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StrategyKind {
+    MaxEntropy,
+    #[default]
+    ExpectedTurns,
+    Minimax,
+    MostProbable,
+    Random,
+    WorstCase,
+    Lookahead,
+}
+
+impl StrategyKind {
+    pub const ALL: [StrategyKind; 7] = [
+        StrategyKind::MaxEntropy,
+        StrategyKind::ExpectedTurns,
+        StrategyKind::Minimax,
+        StrategyKind::MostProbable,
+        StrategyKind::Random,
+        StrategyKind::WorstCase,
+        StrategyKind::Lookahead,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StrategyKind::MaxEntropy => "entropy",
+            StrategyKind::ExpectedTurns => "turns",
+            StrategyKind::Minimax => "minimax",
+            StrategyKind::MostProbable => "probable",
+            StrategyKind::Random => "random",
+            StrategyKind::WorstCase => "worst-case",
+            StrategyKind::Lookahead => "lookahead",
+        }
+    }
+
+    pub fn strategy<const N: usize>(self, model: Model) -> Box<dyn Strategy<N>> {
+        match self {
+            StrategyKind::MaxEntropy => Box::new(MaxEntropy),
+            StrategyKind::ExpectedTurns => Box::new(ExpectedTurns(model)),
+            StrategyKind::Minimax => Box::new(Minimax),
+            StrategyKind::MostProbable => Box::new(MostProbable),
+            StrategyKind::Random => Box::new(RandomAnswer),
+            StrategyKind::WorstCase => Box::new(WorstCase(AdversarialOptions::default())),
+            StrategyKind::Lookahead => Box::new(Lookahead(LookaheadOptions {
+                calibration: model,
+                ..Default::default()
+            })),
+        }
+    }
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown strategy \"{0}\", expected one of: entropy, turns, minimax, probable, random, worst-case, lookahead")]
+pub struct ParseStrategyError(String);
+
+impl FromStr for StrategyKind {
+    type Err = ParseStrategyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StrategyKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| ParseStrategyError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::test_util::abc_dictionary;
+
+    #[rstest]
+    #[case(StrategyKind::MaxEntropy)]
+    #[case(StrategyKind::ExpectedTurns)]
+    #[case(StrategyKind::Minimax)]
+    #[case(StrategyKind::MostProbable)]
+    #[case(StrategyKind::Random)]
+    #[case(StrategyKind::WorstCase)]
+    #[case(StrategyKind::Lookahead)]
+    fn strategies_choose_valid_guesses(#[case] kind: StrategyKind) {
+        let mut dictionary = abc_dictionary();
+        dictionary.probabilities[1] = 2.;
+        let answers = [0, 1, 2, 3];
+        let turn = Turn {
+            dictionary: &dictionary,
+            matrix: None,
+            answers: &answers,
+            history: &[],
+            hard_mode: false,
+            entropies: None,
+        };
+
+        assert_eq!(Ok(kind), kind.name().parse().map_err(|_| ()));
+
+        let guess = kind.strategy(Model::default()).choose(&turn);
+        match kind {
+            StrategyKind::MaxEntropy
+            | StrategyKind::Minimax
+            | StrategyKind::WorstCase
+            | StrategyKind::Lookahead => assert_eq!(4, guess),
+            StrategyKind::MostProbable => assert_eq!(1, guess),
+            _ => assert!(guess < dictionary.words.len()),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use super::Dictionary;
+use crate::{
+    algo::GuessHints,
+    progress::{Cancelled, Progress},
+};
+
+/// Hints of every word of a dictionary guessed against each of its answers,
+/// stored as hint indices
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HintMatrix {
+    /// Column of every word in the matrix, `None` for words which are not answers
+    columns: Vec<Option<usize>>,
+    answers_len: usize,
+    hints: HintIndices,
+}
+
+/// A byte per hint index as long as they fit in it, only the 3^6 and 3^7 hints
+/// of 6- and 7-letter words take 2 bytes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+enum HintIndices {
+    Narrow(Vec<u8>),
+    Wide(Vec<u16>),
+}
+
+/// Hints of all the answers for one of the guesses, see [`HintMatrix::row`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Row<'a> {
+    Narrow(&'a [u8]),
+    Wide(&'a [u16]),
+}
+
+impl Row<'_> {
+    /// Hints index of the answer in the `column`
+    pub fn get(&self, column: usize) -> usize {
+        match self {
+            Row::Narrow(row) => row[column] as usize,
+            Row::Wide(row) => row[column] as usize,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Row::Narrow(row) => row.len(),
+            Row::Wide(row) => row.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl HintMatrix {
+    pub fn new<const N: usize>(dictionary: &Dictionary<N>) -> Self {
+        Self::with_progress(dictionary, &Progress::new())
+            .expect("Matrix can't be cancelled without the progress")
+    }
+
+    /// Same as [`HintMatrix::new`], reporting each of the rows done to the `progress`
+    pub fn with_progress<const N: usize>(
+        dictionary: &Dictionary<N>,
+        progress: &Progress,
+    ) -> Result<Self, Cancelled> {
+        progress.start(dictionary.words.len());
+        let mut columns = vec![None; dictionary.words.len()];
+        for (column, &answer) in dictionary.answers.iter().enumerate() {
+            columns[answer] = Some(column);
+        }
+
+        let hints = if Self::hint_size(N) == 1 {
+            HintIndices::Narrow(hint_rows(dictionary, progress, |ind| ind as u8)?)
+        } else {
+            HintIndices::Wide(hint_rows(dictionary, progress, |ind| ind as u16)?)
+        };
+
+        Ok(Self {
+            columns,
+            answers_len: dictionary.answers.len(),
+            hints,
+        })
+    }
+
+    /// Bytes taken by each of the hints in the matrix of `word_length`-letter words
+    pub fn hint_size(word_length: usize) -> usize {
+        if 3usize.pow(word_length as u32) <= u8::MAX as usize + 1 {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Hints of all the answers for the `guess`, in the order of `Dictionary::answers`
+    pub fn row(&self, guess: usize) -> Row<'_> {
+        let range = guess * self.answers_len..(guess + 1) * self.answers_len;
+        match &self.hints {
+            HintIndices::Narrow(hints) => Row::Narrow(&hints[range]),
+            HintIndices::Wide(hints) => Row::Wide(&hints[range]),
+        }
+    }
+
+    /// Position of the `answer` (index into `Dictionary::words`) in the rows.
+    /// Panics if `answer` is not an answer.
+    pub fn column(&self, answer: usize) -> usize {
+        self.columns[answer].expect("Hints are only stored for the answers")
+    }
+
+    /// Index of the hints given for the `guess` when the solution is `answer`,
+    /// both being indices into `Dictionary::words`. Panics if `answer` is not an answer.
+    pub fn get(&self, guess: usize, answer: usize) -> usize {
+        self.row(guess).get(self.column(answer))
+    }
+}
+
+/// Hint indices of every word against each of the answers, row after row,
+/// converted to the stored type by `convert`
+fn hint_rows<T, const N: usize>(
+    dictionary: &Dictionary<N>,
+    progress: &Progress,
+    convert: fn(usize) -> T,
+) -> Result<Vec<T>, Cancelled>
+where
+    T: Copy + Send,
+{
+    let row = |guess: usize| {
+        progress.check()?;
+        let guess_hints = GuessHints::new(&dictionary.words_bytes[guess]);
+        let row = dictionary
+            .answers
+            .iter()
+            .map(|&answer| convert(guess_hints.ind(&dictionary.words_bytes[answer])))
+            .collect::<Vec<_>>();
+        progress.advance();
+        Ok(row)
+    };
+
+    #[cfg(feature = "parallel")]
+    let rows = (0..dictionary.words.len())
+        .into_par_iter()
+        .map(row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    #[cfg(not(feature = "parallel"))]
+    let rows = (0..dictionary.words.len())
+        .map(row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::{filter_answers, get_answers, get_hints, update_knowledge};
+    use crate::structs::{knowledge::KnowledgeN, WordN};
+
+    #[test]
+    fn matrix_filtering() {
+        let words = ["crane", "trace", "react", "caret", "brave", "grade"]
+            .into_iter()
+            .map(|w| WordN::try_from(w).unwrap())
+            .collect::<Vec<WordN<char, 5>>>();
+        let dictionary = Dictionary::with_answers(words, vec![1.; 6], vec![1, 2, 3, 5]);
+        let matrix = HintMatrix::new(&dictionary);
+
+        let hints = get_hints(&dictionary.words[0], &dictionary.words[2]);
+        assert_eq!(hints.to_ind(), matrix.get(0, 2));
+        assert_eq!(4, matrix.row(0).len());
+        assert!(matches!(matrix.row(0), Row::Narrow(_)));
+
+        let knowledge = update_knowledge(&dictionary.words[0], &hints, KnowledgeN::default());
+        assert_eq!(
+            get_answers(&dictionary.words, &dictionary.answers, &knowledge),
+            filter_answers(&matrix, &dictionary.answers, 0, hints.to_ind())
+        );
+
+        let progress = Progress::new();
+        assert_eq!(
+            Ok(&matrix),
+            HintMatrix::with_progress(&dictionary, &progress).as_ref()
+        );
+        assert_eq!((6, 6), (progress.done(), progress.total()));
+        progress.cancel();
+        assert_eq!(
+            Err(Cancelled),
+            HintMatrix::with_progress(&dictionary, &progress)
+        );
+    }
+
+    #[test]
+    fn wide_matrix() {
+        let words = ["planet", "plaice", "tablet"]
+            .into_iter()
+            .map(|w| WordN::try_from(w).unwrap())
+            .collect::<Vec<WordN<char, 6>>>();
+        let dictionary = Dictionary::new(words, vec![1.; 3]);
+        let matrix = HintMatrix::new(&dictionary);
+
+        assert!(matches!(matrix.row(0), Row::Wide(_)));
+        for guess in 0..3 {
+            for answer in 0..3 {
+                let hints = get_hints(&dictionary.words[guess], &dictionary.words[answer]);
+                assert_eq!(hints.to_ind(), matrix.get(guess, answer));
+            }
+        }
+    }
+}
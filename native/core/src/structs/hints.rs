@@ -1,4 +1,4 @@
-use std::{iter::repeat, str::FromStr};
+use std::str::FromStr;
 
 #[cfg(feature = "terminal")]
 use colored::Colorize;
@@ -37,9 +37,9 @@ impl FromStr for Hint {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.chars().next().map(|x| x.to_ascii_lowercase()) {
-            Some(x) if x == 'w' => Ok(Hint::Wrong),
-            Some(x) if x == 'o' => Ok(Hint::OutOfPlace),
-            Some(x) if x == 'c' => Ok(Hint::Correct),
+            Some('w') => Ok(Hint::Wrong),
+            Some('o') => Ok(Hint::OutOfPlace),
+            Some('c') => Ok(Hint::Correct),
             _ => Err("Wrong character"),
         }
     }
@@ -184,14 +184,12 @@ pub struct ValidHints(pub Vec<Vec<Hint>>);
 
 impl ValidHints {
     pub fn empty(n: usize) -> Self {
-        let vec = repeat(vec![]).take(n).collect();
+        let vec = repeat_n(vec![], n).collect();
         Self(vec)
     }
 
     pub fn any(n: usize) -> Self {
-        let vec = repeat(vec![Hint::Wrong, Hint::OutOfPlace, Hint::Correct])
-            .take(n)
-            .collect();
+        let vec = repeat_n(vec![Hint::Wrong, Hint::OutOfPlace, Hint::Correct], n).collect();
         Self(vec)
     }
 }
@@ -26,8 +26,7 @@ impl<const N: usize> Default for PartialWord<N> {
 impl<const N: usize> PartialWord<N> {
     pub fn new() -> Self {
         Self {
-            word: iter::repeat(PartialChar::None)
-                .take(N)
+            word: iter::repeat_n(PartialChar::None, N)
                 .collect::<Vec<_>>()
                 .try_into()
                 .unwrap(),
@@ -51,7 +50,12 @@ impl<const N: usize> TryFrom<Vec<PartialChar>> for PartialWord<N> {
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct KnowledgeN<const N: usize> {
+    /// Minimum number of occurrences of the letters
     pub known: FxHashMap<char, u8>,
+    /// Maximum number of occurrences of the letters, revealed by a gray hint.
+    /// The count is exact when it's the same as in `known`.
+    #[serde(default)]
+    pub max_counts: FxHashMap<char, u8>,
     pub ruled_out: HashSet<char>,
     pub placed: PartialWord<N>,
 }
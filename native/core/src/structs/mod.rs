@@ -1,47 +1,244 @@
+pub mod hint_matrix;
 pub mod hints;
 pub mod knowledge;
 pub mod word;
 
 use crate::translator::Translator;
+pub use hint_matrix::HintMatrix;
+use hints::Hint;
 pub use hints::HintsN;
 use serde::{Deserialize, Serialize};
 pub use word::{WordError, WordN};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct EntropiesData<const N: usize> {
+pub struct EntropiesData {
     pub entropy: f64,
+    /// Probability of each of the hints, empty in the summaries of the guesses
     pub probabilities: Vec<f64>,
+    /// Number of the answers left in the worst case
+    #[serde(default)]
+    pub max_bucket: usize,
+    /// Number of the answers left on average
+    #[serde(default)]
+    pub expected_size: f64,
+    /// Number of the distinct hints the guess can get
+    #[serde(default)]
+    pub buckets: usize,
+    /// Probability of the guess being the answer
+    #[serde(default)]
+    pub solve_probability: f64,
 }
 
-impl<const N: usize> EntropiesData<N> {
-    pub fn new(entropy: f64, probabilities: Vec<f64>) -> Self {
+impl EntropiesData {
+    /// `probabilities` and `counts` hold the probability and the number of the answers
+    /// giving each of the hints of the `N`-letter words
+    pub fn new<const N: usize>(entropy: f64, probabilities: Vec<f64>, counts: &[u32]) -> Self {
+        let summary = Self::summary::<N>(entropy, &probabilities, counts);
+        Self {
+            probabilities,
+            ..summary
+        }
+    }
+
+    /// Same as [`EntropiesData::new`], without keeping the `probabilities`
+    pub fn summary<const N: usize>(entropy: f64, probabilities: &[f64], counts: &[u32]) -> Self {
+        let expected_size = probabilities
+            .iter()
+            .zip(counts)
+            .map(|(probability, &count)| probability * count as f64)
+            .sum();
+
         EntropiesData {
             entropy,
-            probabilities,
+            max_bucket: counts.iter().copied().max().unwrap_or_default() as usize,
+            expected_size,
+            buckets: counts.iter().filter(|&&count| count > 0).count(),
+            solve_probability: probabilities[HintsN::<N>::correct().to_ind()],
+            probabilities: vec![],
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(from = "StoredDictionary<N>")]
 pub struct Dictionary<const N: usize> {
     pub words: Vec<WordN<char, N>>,
     pub words_bytes: Vec<WordN<u8, N>>,
+    pub answers: Vec<usize>,
     pub hints: Vec<HintsN<N>>,
     pub probabilities: Vec<f64>,
     pub translator: Translator,
 }
 
+/// [`Dictionary`] as saved, possibly before the answers were separated from the guesses
+#[derive(Deserialize)]
+struct StoredDictionary<const N: usize> {
+    words: Vec<WordN<char, N>>,
+    words_bytes: Vec<WordN<u8, N>>,
+    #[serde(default)]
+    answers: Vec<usize>,
+    hints: Vec<HintsN<N>>,
+    probabilities: Vec<f64>,
+    translator: Translator,
+}
+
+/// Every word of a dictionary saved without the answers can be the answer
+impl<const N: usize> From<StoredDictionary<N>> for Dictionary<N> {
+    fn from(stored: StoredDictionary<N>) -> Self {
+        let answers = if stored.answers.is_empty() {
+            (0..stored.words.len()).collect()
+        } else {
+            stored.answers
+        };
+
+        Self {
+            words: stored.words,
+            words_bytes: stored.words_bytes,
+            answers,
+            hints: stored.hints,
+            probabilities: stored.probabilities,
+            translator: stored.translator,
+        }
+    }
+}
+
 impl<const N: usize> Dictionary<N> {
     pub fn new(words: Vec<WordN<char, N>>, probabilities: Vec<f64>) -> Self {
+        let answers = (0..words.len()).collect();
+        Self::with_answers(words, probabilities, answers)
+    }
+
+    /// `words` are all the allowed guesses, `answers` are indices into `words`
+    /// of the ones that can also be a solution
+    pub fn with_answers(
+        words: Vec<WordN<char, N>>,
+        probabilities: Vec<f64>,
+        answers: Vec<usize>,
+    ) -> Self {
         let translator = Translator::generate(&words);
         let words_bytes = words.iter().map(|w| translator.to_bytes(w)).collect();
         let hints = HintsN::<N>::all();
         Self {
             words,
             words_bytes,
+            answers,
             hints,
             probabilities,
             translator,
         }
     }
+
+    pub fn word_length(&self) -> usize {
+        N
+    }
+}
+
+/// Word lengths supported by [`AnyDictionary`]
+pub const WORD_LENGTHS: [usize; 4] = [4, 5, 6, 7];
+
+/// Dictionary with the word length only known at runtime, e.g. after loading it from a file.
+/// Use [`dispatch_dictionary`](crate::dispatch_dictionary) to work with the inner dictionary.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AnyDictionary {
+    Four(Dictionary<4>),
+    Five(Dictionary<5>),
+    Six(Dictionary<6>),
+    Seven(Dictionary<7>),
+}
+
+impl AnyDictionary {
+    pub fn word_length(&self) -> usize {
+        crate::dispatch_dictionary!(self, dictionary => dictionary.word_length())
+    }
+
+    /// Number of the allowed guesses
+    pub fn words_len(&self) -> usize {
+        crate::dispatch_dictionary!(self, dictionary => dictionary.words.len())
+    }
+
+    pub fn word(&self, i: usize) -> String {
+        crate::dispatch_dictionary!(self, dictionary => dictionary.words[i].to_string())
+    }
+
+    /// Index of the `word` among the allowed guesses
+    pub fn position(&self, word: &str) -> Option<usize> {
+        crate::dispatch_dictionary!(self, dictionary => {
+            let word = WordN::try_from(word).ok()?;
+            dictionary.words.iter().position(|w| *w == word)
+        })
+    }
+
+    /// Hints with the index `i`, as in [`HintsN::to_ind`]
+    pub fn hints(&self, i: usize) -> Vec<Hint> {
+        crate::dispatch_dictionary!(self, dictionary => dictionary.hints[i].0.to_vec())
+    }
+
+    pub fn answers(&self) -> &[usize] {
+        crate::dispatch_dictionary!(self, dictionary => &dictionary.answers)
+    }
+
+    pub fn probabilities(&self) -> &[f64] {
+        crate::dispatch_dictionary!(self, dictionary => &dictionary.probabilities)
+    }
+}
+
+/// Evaluates `$body` with `$dictionary` bound to the inner `Dictionary<N>`
+/// of an [`AnyDictionary`], monomorphizing the body for every supported word length
+#[macro_export]
+macro_rules! dispatch_dictionary {
+    ($any_dictionary:expr, $dictionary:ident => $body:expr) => {
+        match $any_dictionary {
+            $crate::structs::AnyDictionary::Four($dictionary) => $body,
+            $crate::structs::AnyDictionary::Five($dictionary) => $body,
+            $crate::structs::AnyDictionary::Six($dictionary) => $body,
+            $crate::structs::AnyDictionary::Seven($dictionary) => $body,
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dictionary_without_answers() {
+        let words = ["abcd", "abce", "xefg"]
+            .into_iter()
+            .map(|w| WordN::try_from(w).unwrap())
+            .collect::<Vec<WordN<char, 4>>>();
+        let dictionary = Dictionary::with_answers(words, vec![1.; 3], vec![1]);
+
+        let mut json = serde_json::to_value(&dictionary).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        // the hints are printed in colour with the terminal feature, they are not checked here
+        fields.insert("hints".to_string(), serde_json::Value::Array(vec![]));
+
+        let stored: Dictionary<4> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(vec![1], stored.answers);
+
+        // saved before the answers were kept separately
+        json.as_object_mut().unwrap().remove("answers");
+        let stored: Dictionary<4> = serde_json::from_value(json).unwrap();
+        assert_eq!(vec![0, 1, 2], stored.answers);
+        assert_eq!(dictionary.words, stored.words);
+    }
+
+    #[test]
+    fn any_dictionary_accessors() {
+        let words = ["abcdef", "abcdeg"]
+            .into_iter()
+            .map(|w| WordN::try_from(w).unwrap())
+            .collect::<Vec<WordN<char, 6>>>();
+        let dictionary =
+            AnyDictionary::Six(Dictionary::with_answers(words, vec![0.3, 0.7], vec![1]));
+
+        assert_eq!(6, dictionary.word_length());
+        assert_eq!(2, dictionary.words_len());
+        assert_eq!("abcdeg", dictionary.word(1));
+        assert_eq!(Some(1), dictionary.position("abcdeg"));
+        assert_eq!(None, dictionary.position("abcde"));
+        assert_eq!(vec![Hint::Correct; 6], dictionary.hints(728));
+        assert_eq!(&[1], dictionary.answers());
+        assert_eq!(&[0.3, 0.7], dictionary.probabilities());
+    }
 }
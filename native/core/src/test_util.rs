@@ -0,0 +1,18 @@
+use crate::structs::{Dictionary, WordN};
+
+/// Only "xefg" tells apart all of the "abc?" words, "abcd" and "dxyz" only split "abcd" off
+pub const ABC_WORDS: [&str; 6] = ["abcd", "abce", "abcf", "abcg", "xefg", "dxyz"];
+
+/// Dictionary of [`ABC_WORDS`], all of them equally likely answers
+pub fn abc_dictionary() -> Dictionary<4> {
+    abc_dictionary_with_answers((0..ABC_WORDS.len()).collect())
+}
+
+/// Dictionary of [`ABC_WORDS`] with only the `answers` being possible answers
+pub fn abc_dictionary_with_answers(answers: Vec<usize>) -> Dictionary<4> {
+    let words = ABC_WORDS
+        .into_iter()
+        .map(|w| WordN::try_from(w).unwrap())
+        .collect::<Vec<_>>();
+    Dictionary::with_answers(words, vec![1.; ABC_WORDS.len()], answers)
+}
@@ -16,11 +16,11 @@ impl Translator {
 
         for word in words {
             for c in word.0 {
-                if !char_to_u8.contains_key(&c) {
-                    char_to_u8.insert(c, u);
+                char_to_u8.entry(c).or_insert_with(|| {
                     u8_to_char.push(c);
                     u += 1;
-                }
+                    u - 1
+                });
             }
         }
 
@@ -39,6 +39,10 @@ impl Translator {
         word
     }
 
+    pub fn to_byte(&self, c: char) -> Option<u8> {
+        self.char_to_u8.get(&c).copied()
+    }
+
     pub fn to_bytes<const N: usize>(&self, word: &WordN<char, N>) -> WordN<u8, N> {
         let mut byte_word = WordN::init(0);
         for (i, c) in word.0.iter().enumerate() {
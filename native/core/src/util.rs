@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-pub fn print_vec<T: Display>(words: &Vec<T>) {
+pub fn print_vec<T: Display>(words: &[T]) {
     for word in words {
         println!("{word}")
     }
@@ -5,7 +5,7 @@ import sys
 class RequestHandler(SimpleHTTPRequestHandler):
     def do_GET(self):
         print(self.path)
-        if self.path.endswith(('.js', '.css', '.wasm', '.txt')):
+        if self.path.endswith(('.js', '.css', '.wasm', '.txt', '.json', '.csv')):
             super().do_GET()
         else:
             self.send_response(200)
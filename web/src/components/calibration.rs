@@ -1,118 +1,181 @@
-use bounce::use_slice_dispatch;
-use wordle_entropy_core::calibration::{fit, Calibration as CalibrationParameters};
-use yew::{function_component, html, Callback, Properties};
+use std::rc::Rc;
+use std::str::FromStr;
+
+use anyhow::Result;
+use bounce::{use_atom_setter, use_slice_dispatch};
+use gloo_file::{
+    callbacks::{read_as_text, FileReader},
+    FileReadError,
+};
+use web_sys::HtmlInputElement;
+use wordle_entropy_core::calibration::{
+    bin_data, compare_models, CalibrationFile, Model, ModelComparison, ModelFamily, TurnsModel,
+};
+use yew::{
+    function_component, html, use_mut_ref, use_state, Callback, Event, Properties, TargetCast,
+};
 
 use crate::{
-    components::Plot,
+    components::{Plot, ToastOption, ToastType},
     plots::TurnsLeftPlotter,
     word_set::{SetCalibration, WordSetVec, WordSetVecAction},
 };
 
-fn calibrate(bar_data: &Vec<((f64, f64), f64)>) -> Option<CalibrationParameters> {
-    let fit_data = bar_data
-        .into_iter()
-        .map(|&((x0, x1), y)| (0.5 * (x0 + x1), y))
-        .collect::<Vec<_>>();
-
-    fit(fit_data, None).ok()
+/// Calibration saved by the `calibrate` command of the binary
+fn load_calibration(content: std::result::Result<String, FileReadError>) -> Result<Model> {
+    let file: CalibrationFile = serde_json::from_str(&content?)?;
+    Ok(file.model())
 }
 
-fn calc_bar_data(
-    data_with_weights: &Vec<(f64, f64, f64)>,
-    bar_per_1: i32,
-) -> Vec<((f64, f64), f64)> {
-    let data = data_with_weights
-        .into_iter()
-        .copied()
-        .map(|(c1, c2, _)| (c1, c2))
-        .collect::<Vec<_>>();
-
-    let x_max = 1.
-        + data
-            .iter()
-            .map(|&(entropy, _)| entropy)
-            .max_by(|x, y| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Less))
-            .unwrap_or(5.) as f64;
-
-    let x_max_i = x_max.ceil() as i32;
-
-    let mut bars = (0..(bar_per_1 * x_max_i))
-        .map(|i| {
-            (
-                (
-                    i as f64 / bar_per_1 as f64,
-                    (i + 1) as f64 / bar_per_1 as f64,
-                ),
-                Vec::<(f64, f64)>::new(),
-            )
-        })
-        .collect::<Vec<_>>();
-
-    for &(x, y, prob) in data_with_weights.iter() {
-        if let Some((_, bar_vec)) = bars.get_mut((x * bar_per_1 as f64).floor() as usize) {
-            bar_vec.push((y, prob));
-        }
-    }
-
-    let bars = bars
-        .into_iter()
-        .filter(|(_, bar_vec)| bar_vec.len() > 0)
-        .collect::<Vec<_>>();
-
-    let bar_data = bars
-        .into_iter()
-        .map(|(x, bar_vec)| {
-            let norm: f64 = bar_vec.iter().map(|&(_, prob)| prob).sum();
-            (
-                x,
-                bar_vec.into_iter().map(|(y, prob)| prob * y).sum::<f64>() / norm,
-            )
-        })
-        .collect::<Vec<_>>();
+/// Binned data along with the models fitted to the bins
+type Fits = (Vec<((f64, f64), f64)>, Vec<ModelComparison>);
 
-    bar_data
-}
+/// Data and bin width the fits were made for, along with the fits
+type CachedFits = Option<(Vec<(f64, f64, f64)>, i32, Rc<Fits>)>;
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub data: Vec<(f64, f64, f64)>,
     pub word_set_name: String,
-    pub used_calibration: CalibrationParameters,
+    pub used_calibration: Model,
 }
 
 #[function_component(Calibration)]
 pub fn view(props: &Props) -> Html {
     let dispatch_word_sets = use_slice_dispatch::<WordSetVec>();
+    let set_toast = use_atom_setter::<ToastOption>();
+    let file_reader = use_mut_ref(|| None::<FileReader>);
+    let cached_fits = use_mut_ref(|| CachedFits::None);
     let bar_per_1 = 4;
-    let bar_data = calc_bar_data(&props.data, bar_per_1);
-    let calibration = calibrate(&bar_data);
+    let family = use_state(ModelFamily::default);
+    // fitting every family takes a while, only done again when the data changes
+    let fits = {
+        let mut cached_fits = cached_fits.borrow_mut();
+        let cached = match &*cached_fits {
+            Some((data, width, fits)) if *data == props.data && *width == bar_per_1 => {
+                Some(fits.clone())
+            }
+            _ => None,
+        };
+        cached.unwrap_or_else(|| {
+            let bar_data = bin_data(&props.data, bar_per_1 as usize);
+            let (comparisons, _) = compare_models(&bar_data);
+            let fits = Rc::new((bar_data, comparisons));
+            *cached_fits = Some((props.data.clone(), bar_per_1, fits.clone()));
+            fits
+        })
+    };
+    let (bar_data, comparisons) = &*fits;
+    let calibration = comparisons
+        .iter()
+        .find(|comparison| comparison.model.family() == *family)
+        .map(|comparison| comparison.model.clone());
+
+    let on_family_change = {
+        let family = family.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(selected) = ModelFamily::from_str(&select.value()) {
+                family.set(selected);
+            }
+        })
+    };
 
     let on_set_calibrate_click = {
         let word_set_name = props.word_set_name.clone();
+        let dispatch_word_sets = dispatch_word_sets.clone();
+        let calibration = calibration.clone();
         Callback::from(move |_| {
-            if let Some(calibration) = calibration {
+            if let Some(calibration) = &calibration {
                 dispatch_word_sets(WordSetVecAction::SetCalibration(
                     word_set_name.clone(),
-                    SetCalibration::Custom(calibration),
+                    SetCalibration::Model(calibration.clone()),
                 ))
             }
         })
     };
 
+    let on_calibration_file_change = {
+        let word_set_name = props.word_set_name.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let file = match input.files().and_then(|files| files.get(0)) {
+                Some(file) => gloo_file::File::from(file),
+                None => return,
+            };
+
+            let word_set_name = word_set_name.clone();
+            let dispatch_word_sets = dispatch_word_sets.clone();
+            let set_toast = set_toast.clone();
+            *file_reader.borrow_mut() = Some(read_as_text(&file, move |content| {
+                match load_calibration(content) {
+                    Ok(calibration) => dispatch_word_sets(WordSetVecAction::SetCalibration(
+                        word_set_name,
+                        SetCalibration::Model(calibration),
+                    )),
+                    Err(err) => set_toast(ToastOption::new(
+                        format!("Loading calibration error: {err}"),
+                        ToastType::Error,
+                    )),
+                }
+            }));
+        })
+    };
+
     let plotter = TurnsLeftPlotter {
         calibration,
-        used_calibration: props.used_calibration,
+        used_calibration: props.used_calibration.clone(),
         bar_per_1,
-        bar_data,
+        bar_data: bar_data.clone(),
     };
 
     html! {
         <>
             <Plot<(f64, f64, f64), TurnsLeftPlotter> data={props.data.clone()} {plotter} />
+            <div class="form-group">
+                <label class="form-label" for="calibration_model">{ "Model" }</label>
+                <select id="calibration_model" class="form-select" onchange={on_family_change}>
+                    {
+                        ModelFamily::ALL.iter().map(|option| html! {
+                            <option value={option.to_string()} selected={option == &*family}>
+                                { option.name() }
+                            </option>
+                        }).collect::<Html>()
+                    }
+                </select>
+            </div>
             <button
                     class="btn btn-primary"
                     onclick={on_set_calibrate_click}
             >{ "Set current calibration" }</button>
+            <table class="table">
+                <thead>
+                    <tr>
+                        <th>{ "Model" }</th>
+                        <th>{ "Parameters" }</th>
+                        <th>{ "R²" }</th>
+                        <th>{ "RMSE" }</th>
+                        <th>{ "AICc" }</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {
+                        comparisons.iter().map(|comparison| html! {
+                            <tr class={(comparison.model.family() == *family).then(|| "active")}>
+                                <td>{ comparison.model.to_string() }</td>
+                                <td>{ comparison.model.parameters() }</td>
+                                <td>{ format!("{:.3}", comparison.stats.r_squared) }</td>
+                                <td>{ format!("{:.3}", comparison.stats.rmse) }</td>
+                                <td>{ format!("{:.2}", comparison.aic) }</td>
+                            </tr>
+                        }).collect::<Html>()
+                    }
+                </tbody>
+            </table>
+            <div class="form-group">
+                <label class="form-label" for="calibration_file">{ "Load calibration file" }</label>
+                <input id="calibration_file" class="form-input" type="file" accept=".json" onchange={on_calibration_file_change} />
+            </div>
         </>
     }
 }
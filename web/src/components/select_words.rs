@@ -2,40 +2,45 @@ use std::{collections::HashSet, rc::Rc};
 
 use anyhow::{anyhow, Error, Result};
 use web_sys::HtmlInputElement;
-use wordle_entropy_core::structs::WordError;
+use wordle_entropy_core::structs::{AnyDictionary, WordError};
 use yew::{
     classes, function_component, html, use_mut_ref, use_state, Callback, Event, InputEvent,
     Properties, TargetCast,
 };
 
-use crate::Dictionary;
-
 #[derive(Clone, Debug)]
 pub enum SelectedWords {
     Random(usize),
     Custom(Vec<usize>),
 }
 
-fn parse_custom_words(words_str: &str, dictionary: &Dictionary) -> Result<Vec<usize>> {
-    let words: Vec<_> = words_str
-        .split(",")
-        .map(|word| word.try_into().map_err(|e: WordError| e.into()))
-        .collect::<Result<_>>()?;
+fn parse_custom_words(words_str: &str, dictionary: &AnyDictionary) -> Result<Vec<usize>> {
+    let words: Vec<_> = words_str.split(",").collect();
+    if let Some(word) = words
+        .iter()
+        .find(|word| word.chars().count() != dictionary.word_length())
+    {
+        return Err(WordError::IncorrectLength {
+            word: word.to_string(),
+            expected_length: dictionary.word_length(),
+        }
+        .into());
+    }
 
-    let mut words_unchecked = words.iter().collect::<HashSet<_>>();
+    let mut words_unchecked = words.into_iter().collect::<HashSet<_>>();
 
     let mut words_inds = vec![];
 
-    for (i, word) in dictionary.words.iter().enumerate() {
-        if words_unchecked.contains(word) {
-            words_unchecked.remove(word);
+    for &i in dictionary.answers().iter() {
+        let word = dictionary.word(i);
+        if words_unchecked.remove(word.as_str()) {
             words_inds.push(i);
         }
     }
 
     if words_unchecked.len() > 0 {
         return Err(anyhow!(
-            "Words not found in the selected word set: {}",
+            "Words not found among the answers of the selected word set: {}",
             words_unchecked
                 .iter()
                 .map(|x| format!("{x}"))
@@ -49,7 +54,7 @@ fn parse_custom_words(words_str: &str, dictionary: &Dictionary) -> Result<Vec<us
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
-    pub dictionary: Rc<Dictionary>,
+    pub dictionary: Rc<AnyDictionary>,
     pub on_words_set: Callback<SelectedWords>,
 }
 
@@ -66,7 +71,7 @@ pub fn view(props: &Props) -> Html {
         let num_random_err = num_random_err.clone();
         let selected = selected.clone();
         let on_words_set = props.on_words_set.clone();
-        let max_words = props.dictionary.words.len();
+        let max_words = props.dictionary.answers().len();
         Callback::from(move |e: InputEvent| {
             let input: HtmlInputElement = e.target_unchecked_into();
             match input
@@ -23,7 +23,7 @@ pub fn render_suggestions(
         .map(|&(word_ind, entropy, score)| {
             //let score = score + step.1.len() as f64 - if ended { 1. } else { 0. };
             let score = score + num_answers_before as f64;
-            let word = &word_set.dictionary.words[word_ind];
+            let word = word_set.dictionary.word(word_ind);
             let possible = answers.contains(&word_ind);
             let row = vec![
                 format!("{word}"),
@@ -165,8 +165,8 @@ pub fn view(props: &Props) -> Html {
 
                                 answers_opt_iter.map(|answer| {
                                     if let Some(&answer) = answer {
-                                        let probability = word_set.dictionary.probabilities[answer];
-                                        let answer = &word_set.dictionary.words[answer];
+                                        let probability = word_set.dictionary.probabilities()[answer];
+                                        let answer = word_set.dictionary.word(answer);
                                         html! {
                                             <tr>
                                                 <td> { format!("{answer}") } </td>
@@ -207,12 +207,12 @@ pub fn view(props: &Props) -> Html {
                                 step.1.iter().enumerate().map(|(i, &GuessStep { guess, hints, .. })| {
                                     let c_selected = selected_step.filter(|&selected_i| selected_i == i).map(|_| "selected".to_string());
 
-                                    let word = &word_set.dictionary.words[guess];
-                                    let hints = &word_set.dictionary.hints[hints];
+                                    let word = word_set.dictionary.word(guess);
+                                    let hints = word_set.dictionary.hints(hints);
                                     html! {
                                         <tr class={classes![c_selected]}>
                                             <td>
-                                                <HintedWord word={word.clone()} hints={hints.clone()} />
+                                                <HintedWord {word} {hints} />
                                             </td>
                                         </tr>
                                     }
@@ -261,7 +261,7 @@ pub fn view(props: &Props) -> Html {
                                     html! {}
                                 }
                             }}} else if let Some(scores) = props.init_scores.clone() {{{
-                                let answers = (0..word_set.dictionary.words.len()).collect::<Vec<_>>();
+                                let answers = word_set.dictionary.answers().to_vec();
                                 render_suggestions(&scores, &answers, word_set.as_ref(), 0)
                             }}} else {
                                 html! {}
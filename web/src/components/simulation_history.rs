@@ -29,7 +29,7 @@ pub fn view(props: &Props) -> Html {
     let data = props
         .history_small
         .iter()
-        .map(|&(turns, word)| (turns, props.word_set.dictionary.probabilities[word]))
+        .map(|&(turns, word)| (turns, props.word_set.dictionary.probabilities()[word]))
         .collect::<Vec<_>>();
     let plotter = ExpectedTurnsPlotter {
         weighted: *weighted_display,
@@ -56,8 +56,8 @@ pub fn view(props: &Props) -> Html {
                         <p class="history">
                             {
                                 row.1.iter().map(|GuessStep { guess, hints, .. }| {
-                                    let word = props.word_set.dictionary.words[*guess].clone();
-                                    let hints = props.word_set.dictionary.hints[*hints].clone();
+                                    let word = props.word_set.dictionary.word(*guess);
+                                    let hints = props.word_set.dictionary.hints(*hints);
                                     html! {
                                         <>
                                             <HintedWord {word} {hints} />
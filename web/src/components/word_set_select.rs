@@ -1,29 +1,102 @@
 use anyhow::{anyhow, Result};
 use bounce::{use_atom, use_atom_setter, use_slice, Atom, UseSliceHandle};
+use gloo_utils::window;
+use reqwest::Url;
 use wasm_bindgen_futures::spawn_local;
 use web_sys::HtmlInputElement;
-use wordle_entropy_core::data::parse_words;
+use wordle_entropy_core::data::LoadOptions;
 use yew::events::Event;
-use yew::{function_component, html, use_effect, Callback, Html, TargetCast};
+use yew::{function_component, html, use_effect_with_deps, Callback, Html, TargetCast};
 
 use super::toast::{ToastOption, ToastType};
-use crate::word_set::{DefaultWordSets, WordSet, WordSetSpec, WordSetVec, WordSetVecAction};
-use crate::WORD_SIZE;
+use crate::storage::{load_word_sets, StoredWordSets};
+use crate::word_set::{
+    bundled_word_sets, parse_dictionary, DefaultWordSets, WordSet, WordSetSpec, WordSetVec,
+    WordSetVecAction,
+};
+
+/// Manifest of the default word sets, relative to the page unless it's an absolute URL.
+/// Can be changed at build time with the `WORDLE_DEFAULT_WORD_SETS_URL` variable.
+const DEFAULT_WORD_SETS_URL: &str = match option_env!("WORDLE_DEFAULT_WORD_SETS_URL") {
+    Some(url) => url,
+    None => "data/default_word_sets.json",
+};
+
+/// Sets the stored word sets, or the default ones if there are none.
+/// Returns the problems with the stored ones, which are skipped.
+async fn handle_word_set_init(word_sets: UseSliceHandle<WordSetVec>) -> Result<Vec<String>> {
+    let mut problems = vec![];
+    let stored = match load_word_sets().await {
+        Ok(StoredWordSets {
+            word_sets: stored,
+            errors,
+        }) => {
+            problems.extend(
+                errors
+                    .into_iter()
+                    .map(|err| format!("Skipped a stored word set that couldn't be read, {err}")),
+            );
+            stored
+        }
+        Err(err) => {
+            log::error!("Failed to load the stored word sets: {err}");
+            problems.push(format!(
+                "Failed to load the stored word sets, the changes won't be saved: {err}"
+            ));
+            vec![]
+        }
+    };
+    if !stored.is_empty() {
+        word_sets.dispatch(WordSetVecAction::Set(WordSetVec(stored)));
+        return Ok(problems);
+    }
 
-async fn handle_word_set_init(word_sets: UseSliceHandle<WordSetVec>) -> Result<()> {
-    let client = reqwest::Client::new();
-    let default_word_sets_url = "https://wordle.realcomplexity.com/data/default_word_sets.json";
-    let response = client.get(default_word_sets_url).send().await?;
+    let legacy = WordSetVec::from_local_storage();
+    if !legacy.0.is_empty() {
+        legacy.clear_local_storage();
+        word_sets.dispatch(WordSetVecAction::Set(legacy));
+        return Ok(problems);
+    }
+
+    let loaded_word_sets = match fetch_default_word_sets().await {
+        Ok(loaded_word_sets) => loaded_word_sets,
+        Err(err) => {
+            log::warn!("Using the bundled word sets, loading the default ones failed: {err}");
+            bundled_word_sets()?
+        }
+    };
+
+    word_sets.dispatch(WordSetVecAction::Set(
+        (*word_sets).extend_with(loaded_word_sets),
+    ));
+
+    Ok(problems)
+}
+
+async fn fetch_text(client: &reqwest::Client, url: Url) -> Result<String> {
+    let response = client.get(url.clone()).send().await?;
 
     if !response.status().is_success() {
         return Err(anyhow!(
-            "Error loading default word sets list from url: {default_word_sets_url}, request status: {}",
+            "Error loading {url}, request status: {}",
             response.status()
         ));
     }
 
-    let text = response.text().await?;
+    Ok(response.text().await?)
+}
+
+/// Word sets listed in the manifest at [`DEFAULT_WORD_SETS_URL`],
+/// with the dictionary URLs relative to the manifest
+async fn fetch_default_word_sets() -> Result<Vec<WordSet>> {
+    let page_url = window()
+        .location()
+        .href()
+        .map_err(|err| anyhow!("Unable to get the page URL: {err:?}"))?;
+    let manifest_url = Url::parse(&page_url)?.join(DEFAULT_WORD_SETS_URL)?;
 
+    let client = reqwest::Client::new();
+    let text = fetch_text(&client, manifest_url.clone()).await?;
     let default_word_sets: DefaultWordSets = serde_json::from_str(&text)?;
 
     let mut loaded_word_sets = vec![];
@@ -34,25 +107,13 @@ async fn handle_word_set_init(word_sets: UseSliceHandle<WordSetVec>) -> Result<(
         dictionary_url,
     } in default_word_sets.word_sets.into_iter()
     {
-        let response = client.get(&dictionary_url).send().await?;
-
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "Error loading word sets named: {name} from url: {dictionary_url}, request status: {}",
-                response.status()
-            ));
-        }
-        let text = response.text().await?;
+        let text = fetch_text(&client, manifest_url.join(&dictionary_url)?).await?;
 
-        let dictionary = parse_words::<_, WORD_SIZE>(text.lines())?;
+        let (dictionary, _) = parse_dictionary(&text, &LoadOptions::default())?;
         loaded_word_sets.push(WordSet::from_dictionary(name, dictionary));
     }
 
-    word_sets.dispatch(WordSetVecAction::Set(
-        (*word_sets).extend_with(loaded_word_sets),
-    ));
-
-    Ok(())
+    Ok(loaded_word_sets)
 }
 
 #[derive(Default, PartialEq, Atom)]
@@ -67,21 +128,24 @@ pub fn word_set_select() -> Html {
     {
         let word_sets = word_sets.clone();
         let set_toast = set_toast.clone();
-        use_effect(move || {
-            if word_sets.0.len() == 0 {
+        use_effect_with_deps(
+            move |_| {
                 spawn_local(async move {
-                    let result = handle_word_set_init(word_sets).await;
-                    if let Err(err) = result {
-                        set_toast(ToastOption::new(
+                    match handle_word_set_init(word_sets).await {
+                        Ok(problems) if !problems.is_empty() => {
+                            set_toast(ToastOption::new(problems.join("\n"), ToastType::Warning))
+                        }
+                        Ok(_) => (),
+                        Err(err) => set_toast(ToastOption::new(
                             format!("{err}").to_string(),
                             ToastType::Error,
-                        ))
+                        )),
                     }
                 });
-            } else {
-            }
-            || ()
-        })
+                || ()
+            },
+            (),
+        )
     }
 
     let onchange = {
@@ -6,8 +6,10 @@ pub mod main_app;
 mod pages;
 mod plots;
 mod simulation;
+mod storage;
 mod util;
 mod word_set;
+mod words;
 mod worker;
 mod worker_atom;
 
@@ -17,13 +19,6 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::{spawn_local, JsFuture};
 pub use wasm_bindgen_rayon::init_thread_pool;
 
-const WORD_SIZE: usize = 5;
-pub type Word = wordle_entropy_core::structs::WordN<char, WORD_SIZE>;
-pub type Hints = wordle_entropy_core::structs::HintsN<WORD_SIZE>;
-pub type Dictionary = wordle_entropy_core::structs::Dictionary<WORD_SIZE>;
-pub type EntropiesData = wordle_entropy_core::structs::EntropiesData<WORD_SIZE>;
-pub type Knowledge = wordle_entropy_core::structs::knowledge::KnowledgeN<WORD_SIZE>;
-
 async fn init_threads() -> Result<JsValue, JsValue> {
     let navigator = Reflect::get(&global(), &JsValue::from_str("navigator"))?;
     let hardware_concurrency = Reflect::get(&navigator, &JsValue::from_str("hardwareConcurrency"))?
@@ -1,5 +1,5 @@
 use crate::components::{ToastComponent, WordSetSelect};
-use crate::pages::{EntropyCalculation, PageNotFound, Simulation, Solver, WordSets};
+use crate::pages::{EntropyCalculation, MultiSolver, PageNotFound, Simulation, Solver, WordSets};
 use bounce::BounceRoot;
 use yew::{function_component, html, Html};
 use yew_router::components::Link;
@@ -17,6 +17,8 @@ pub enum Route {
     Simulation,
     #[at("/solver")]
     Solver,
+    #[at("/multi-solver")]
+    MultiSolver,
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -42,6 +44,9 @@ pub fn view() -> Html {
                         <Link<Route> classes="btn btn-link" to={Route::Solver}>
                             { "Solver" }
                         </Link<Route>>
+                        <Link<Route> classes="btn btn-link" to={Route::MultiSolver}>
+                            { "Multi-board Solver" }
+                        </Link<Route>>
                     </section>
                     <section>
                         <WordSetSelect />
@@ -69,6 +74,9 @@ fn switch(routes: &Route) -> Html {
         Route::Solver {} => {
             html! { <Solver /> }
         }
+        Route::MultiSolver {} => {
+            html! { <MultiSolver /> }
+        }
         Route::NotFound => {
             html! { <PageNotFound /> }
         }
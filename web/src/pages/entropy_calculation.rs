@@ -1,7 +1,10 @@
 use std::rc::Rc;
+use std::str::FromStr;
 
 use bounce::{use_atom_setter, use_slice_dispatch};
 use web_sys::{HtmlElement, HtmlInputElement};
+use wordle_entropy_core::entropy::ScoreKind;
+use wordle_entropy_core::structs::EntropiesData;
 use yew::{
     classes, function_component, html, use_effect_with_deps, Callback, Html, Reducible, TargetCast,
 };
@@ -13,7 +16,16 @@ use crate::word_set::get_current_word_set;
 use crate::word_set::{WordSetVec, WordSetVecAction};
 use crate::worker::{WordleWorkerInput, WordleWorkerOutput};
 use crate::worker_atom::WordleWorkerAtom;
-use crate::EntropiesData;
+
+/// Scores shown in the table, in the order of the columns
+const SCORE_COLUMNS: [(ScoreKind, &str); 6] = [
+    (ScoreKind::Entropy, "Exp. Entropy"),
+    (ScoreKind::ExpectedTurns, "Exp. Turns left"),
+    (ScoreKind::MaxBucket, "Max. Bucket"),
+    (ScoreKind::ExpectedSize, "Exp. Answers left"),
+    (ScoreKind::Buckets, "Buckets"),
+    (ScoreKind::SolveProbability, "Solve Probability"),
+];
 
 enum EntropyStateAction {
     Ready,
@@ -24,6 +36,7 @@ enum EntropyStateAction {
     ),
     StartRunning,
     StopRunning,
+    Progress(usize, usize),
 }
 #[derive(Clone, PartialEq)]
 struct EntropyState {
@@ -31,6 +44,8 @@ struct EntropyState {
     ready: bool,
     word: Option<usize>,
     data: Vec<f64>,
+    /// Guesses done out of the total, while the entropies are calculated
+    progress: Option<(usize, usize)>,
 }
 
 impl EntropyState {
@@ -40,6 +55,7 @@ impl EntropyState {
             ready: ready,
             word: Some(word),
             data,
+            progress: None,
         }
     }
 
@@ -49,12 +65,17 @@ impl EntropyState {
             ready,
             word: None,
             data: vec![],
+            progress: None,
         }
     }
 
     fn with_running(&self, running: bool) -> Self {
         let rest = (*self).clone();
-        Self { running, ..rest }
+        Self {
+            running,
+            progress: None,
+            ..rest
+        }
     }
 
     fn with_ready(&self, ready: bool) -> Self {
@@ -93,6 +114,13 @@ impl Reducible for EntropyState {
             }
             EntropyStateAction::StartRunning => Rc::new(self.with_running(true)),
             EntropyStateAction::StopRunning => Rc::new(self.with_running(false)),
+            EntropyStateAction::Progress(done, total) => {
+                let rest = (*self).clone();
+                Rc::new(Self {
+                    progress: Some((done, total)),
+                    ..rest
+                })
+            }
         }
     }
 }
@@ -133,7 +161,7 @@ pub fn view() -> Html {
 
         move |output: WordleWorkerOutput| match output {
             WordleWorkerOutput::SetWordSet(_) => selected_state.dispatch(EntropyStateAction::Ready),
-            WordleWorkerOutput::Entropy(name, entropies_output) => {
+            WordleWorkerOutput::Entropy(name, entropies_output, matrix) => {
                 let entropies = Rc::new(entropies_output);
                 let word = entropies.iter().next().map(|&(word, _, _)| Some(word));
                 if let Some(word) = word {
@@ -146,8 +174,35 @@ pub fn view() -> Html {
                     selected_state.dispatch(EntropyStateAction::StopRunning);
                 }
 
+                if let Some(matrix) = matrix {
+                    dispatch_word_sets(WordSetVecAction::SetMatrix(name.clone(), matrix));
+                }
                 dispatch_word_sets(WordSetVecAction::SetEntropy(name, entropies));
             }
+            WordleWorkerOutput::Progress { done, total } => {
+                selected_state.dispatch(EntropyStateAction::Progress(done, total))
+            }
+            WordleWorkerOutput::Cancelled => {
+                selected_state.dispatch(EntropyStateAction::StopRunning);
+                set_toast(ToastOption::new(
+                    "Calculation cancelled".to_string(),
+                    ToastType::Info,
+                ));
+            }
+            WordleWorkerOutput::Strategy(name, strategy, matrix) => {
+                selected_state.dispatch(EntropyStateAction::StopRunning);
+                set_toast(ToastOption::new(
+                    format!(
+                        "Heuristic strategy found, {:.3} guesses expected",
+                        strategy.expected_turns
+                    ),
+                    ToastType::Success,
+                ));
+                if let Some(matrix) = matrix {
+                    dispatch_word_sets(WordSetVecAction::SetMatrix(name.clone(), matrix));
+                }
+                dispatch_word_sets(WordSetVecAction::SetStrategy(name, Rc::new(strategy)));
+            }
             WordleWorkerOutput::Err(err) => {
                 selected_state.dispatch(EntropyStateAction::StopRunning);
                 set_toast(ToastOption::new(
@@ -190,6 +245,21 @@ pub fn view() -> Html {
         })
     };
 
+    let onclick_cancel = {
+        let worker = worker.clone();
+        Callback::from(move |_| worker.send(WordleWorkerInput::Cancel))
+    };
+
+    let onclick_strategy = {
+        let worker = worker.clone();
+        let word_set = word_set.clone();
+        let selected_state = selected_state.clone();
+        Callback::from(move |_| {
+            worker.send(WordleWorkerInput::Strategy(word_set.name.clone()));
+            selected_state.dispatch(EntropyStateAction::StartRunning);
+        })
+    };
+
     let onclick_word = {
         let word_set = word_set.clone();
         let selected_state = selected_state.clone();
@@ -225,6 +295,19 @@ pub fn view() -> Html {
         })
     };
 
+    let sort = use_state_eq(ScoreKind::default);
+    let onclick_sort = {
+        let sort = sort.clone();
+        Callback::from(move |e: MouseEvent| {
+            let element: HtmlElement = e.target_unchecked_into();
+            if let Some(kind) = element.dataset().get("sort") {
+                if let Ok(kind) = ScoreKind::from_str(&kind) {
+                    sort.set(kind);
+                }
+            }
+        })
+    };
+
     let selected_word_val = selected_state.word.clone();
     let running = selected_state.running;
     let ready = selected_state.ready;
@@ -235,11 +318,21 @@ pub fn view() -> Html {
             <div class="columns">
                 <div class="column col-6 col-mx-auto">
                     <button class="btn btn-primary" disabled={running || !ready} onclick={onclick_run}>{"Run"}</button>
+                    <button class="btn btn-primary ml-2" disabled={running || !ready} onclick={onclick_strategy}>{"Heuristic strategy"}</button>
                     {
-                        if running {
-                            html!(<div class="d-inline-block loading ml-2"></div>)
-                        } else {
-                            html!()
+                        match (running, selected_state.progress) {
+                            (true, Some((done, total))) => html! {
+                                <>
+                                    <button class="btn ml-2" onclick={onclick_cancel}>{"Cancel"}</button>
+                                    if total > 0 {
+                                        <progress class="progress" value={done.to_string()} max={total.to_string()} />
+                                    } else {
+                                        <progress class="progress" max="100" />
+                                    }
+                                </>
+                            },
+                            (true, None) => html!(<div class="d-inline-block loading ml-2"></div>),
+                            (false, _) => html!(),
                         }
                     }
                 </div>
@@ -267,21 +360,31 @@ pub fn view() -> Html {
                     <div class="words_entropies_list">
                         <table class="table" onclick={onclick_word}>
                             <thead>
-                                <tr>
+                                <tr onclick={onclick_sort}>
                                     <th>{"Word"}</th>
-                                    <th>{"Exp. Entropy"}</th>
-                                    <th>{"Exp. Turns left"}</th>
+                                    {
+                                        SCORE_COLUMNS.iter().map(|&(kind, title)| html! {
+                                            <th
+                                                class={classes!("c-hand", (kind == *sort).then(|| "text-primary"))}
+                                                data-sort={kind.name()}
+                                            >{ title }</th>
+                                        }).collect::<Html>()
+                                    }
                                     <th>{"Rel. Probability"}</th>
                                 </tr>
                             </thead>
                             <tbody>
                             {
                                 if let Some(ref entropies) = word_set.entropies {
+                                    let mut entropies = entropies.iter().collect::<Vec<_>>();
+                                    entropies.sort_by(|(_, entropies_data1, turns1), (_, entropies_data2, turns2)| {
+                                        sort.compare((entropies_data1, *turns1), (entropies_data2, *turns2))
+                                    });
                                     entropies
-                                        .iter()
+                                        .into_iter()
                                         .filter_map(|(word, entropy_data, left_turns)| {
                                             let entropy = &entropy_data.entropy;
-                                            let word_str = &word_set.dictionary.words[*word].to_string();
+                                            let word_str = &word_set.dictionary.word(*word);
                                             if word_str.contains(&*filter) {
                                                 Some(html! {
                                                     <tr
@@ -294,7 +397,11 @@ pub fn view() -> Html {
                                                         <td data-word={word.to_string()}> { word_str }</td>
                                                         <td data-word={word.to_string()}> { format!("{entropy:.3}") } </td>
                                                         <td data-word={word.to_string()}> { format!("{left_turns:.3}") } </td>
-                                                        <td data-word={word.to_string()}> { format!("{:.3}", &word_set.dictionary.probabilities[*word]) } </td>
+                                                        <td data-word={word.to_string()}> { entropy_data.max_bucket } </td>
+                                                        <td data-word={word.to_string()}> { format!("{:.3}", entropy_data.expected_size) } </td>
+                                                        <td data-word={word.to_string()}> { entropy_data.buckets } </td>
+                                                        <td data-word={word.to_string()}> { format!("{:.3}", entropy_data.solve_probability) } </td>
+                                                        <td data-word={word.to_string()}> { format!("{:.3}", &word_set.dictionary.probabilities()[*word]) } </td>
                                                     </tr>
                                                 })
                                             } else {
@@ -1,10 +1,12 @@
 mod entropy_calculation;
+mod multi_solver;
 mod page_not_found;
 mod simulation;
 mod solver;
 mod word_sets;
 
 pub use entropy_calculation::EntropyCalculation;
+pub use multi_solver::MultiSolver;
 pub use page_not_found::PageNotFound;
 pub use simulation::{GuessStep, Simulation};
 pub use solver::Solver;
@@ -0,0 +1,379 @@
+use std::rc::Rc;
+
+use bounce::use_atom_setter;
+use serde_cbor::ser::to_vec_packed;
+use web_sys::{HtmlElement, HtmlInputElement};
+use wordle_entropy_core::multi_board::MultiBoardScore;
+use wordle_entropy_core::structs::hints::{Hint, ValidHints};
+use yew::{
+    classes, function_component, html, use_effect_with_deps, use_reducer, use_state_eq, Callback,
+    Event, Html, InputEvent, MouseEvent, Reducible, TargetCast,
+};
+
+use crate::components::{HintedWord, ToastOption, ToastType};
+use crate::word_set::{get_current_word_set, WordSet};
+use crate::words::{
+    hints_ind, multi_board_states, multi_board_valid_hints, parse_word, BoardState,
+};
+use crate::worker::{WordleWorkerInput, WordleWorkerOutput};
+use crate::worker_atom::WordleWorkerAtom;
+
+const BOARD_COUNTS: [usize; 3] = [2, 4, 8];
+
+enum MultiSolverAction {
+    Reset(Rc<WordSet>, usize),
+    NewWord(String, Rc<WordSet>),
+    /// Board and position of the hint
+    ToggleHint(usize, usize, Rc<WordSet>),
+    Enter(Rc<WordSet>),
+}
+
+#[derive(Clone, PartialEq)]
+struct MultiSolverState {
+    word_set_name: String,
+    boards: Vec<BoardState>,
+    /// Guesses so far, each with the hints of every board
+    history: Vec<(usize, Vec<usize>)>,
+    word_ind: Option<usize>,
+    word: String,
+    hints: Vec<Vec<Hint>>,
+    error: Option<String>,
+}
+
+impl MultiSolverState {
+    pub fn initialize(word_set: &WordSet, boards: usize) -> Self {
+        let dictionary = &word_set.dictionary;
+        let word_length = dictionary.word_length();
+        let (word_ind, word) = if dictionary.words_len() > 0 {
+            (Some(0), dictionary.word(0))
+        } else {
+            (None, " ".repeat(word_length))
+        };
+        let board = BoardState {
+            answers: dictionary.answers().len(),
+            solved: false,
+        };
+        Self {
+            word_set_name: word_set.name.clone(),
+            boards: vec![board; boards],
+            history: vec![],
+            word_ind,
+            word,
+            hints: vec![vec![Hint::Wrong; word_length]; boards],
+            error: None,
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.boards.iter().all(|board| board.solved)
+    }
+}
+
+impl Reducible for MultiSolverState {
+    type Action = MultiSolverAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            MultiSolverAction::Reset(word_set, boards) => {
+                return MultiSolverState::initialize(&word_set, boards).into()
+            }
+            MultiSolverAction::NewWord(new_word, word_set) => {
+                match parse_word(new_word.as_str(), &word_set.dictionary) {
+                    Ok(i) => {
+                        state.word_ind = Some(i);
+                        state.word = word_set.dictionary.word(i);
+                        state.error = None;
+                    }
+                    Err(err) => state.error = Some(err.to_string()),
+                }
+            }
+            MultiSolverAction::ToggleHint(board, i, word_set) => {
+                let valid_hints = match state.word_ind {
+                    Some(guess) => multi_board_valid_hints(
+                        &word_set.dictionary,
+                        state.boards.len(),
+                        &state.history,
+                        board,
+                        guess,
+                        &state.hints[board],
+                    ),
+                    None => ValidHints::any(state.hints[board].len()),
+                };
+                let hints = &mut state.hints[board];
+                let valid = &valid_hints.0[i];
+                let hint_pos = valid.iter().position(|&x| x == hints[i]).unwrap_or(0);
+
+                hints[i] = if valid.is_empty() {
+                    Hint::Wrong
+                } else {
+                    valid[(hint_pos + 1) % valid.len()]
+                };
+            }
+            MultiSolverAction::Enter(word_set) => match state.word_ind {
+                Some(guess) => {
+                    let mut history = state.history.clone();
+                    history.push((guess, state.hints.iter().map(|h| hints_ind(h)).collect()));
+                    match multi_board_states(&word_set.dictionary, state.boards.len(), &history) {
+                        Ok(boards) => {
+                            let word_length = word_set.dictionary.word_length();
+                            state.hints = vec![vec![Hint::Wrong; word_length]; boards.len()];
+                            state.boards = boards;
+                            state.history = history;
+                            state.error = None;
+                        }
+                        Err(err) => state.error = Some(err.to_string()),
+                    }
+                }
+                None => state.error = Some("Guess is not a valid word!".to_string()),
+            },
+        }
+
+        state.into()
+    }
+}
+
+#[function_component(MultiSolver)]
+pub fn view() -> Html {
+    let word_set = Rc::new(get_current_word_set());
+    let set_toast = use_atom_setter::<ToastOption>();
+    let boards = use_state_eq(|| 4);
+    let state = use_reducer(|| MultiSolverState::initialize(&word_set, *boards));
+    let scores = use_state_eq(Vec::<MultiBoardScore>::new);
+
+    let cb = {
+        let set_toast = set_toast.clone();
+        let scores = scores.clone();
+
+        move |output: WordleWorkerOutput| match output {
+            WordleWorkerOutput::SetWordSet(_name) => (),
+            WordleWorkerOutput::MultiBoard(new_scores) => scores.set(new_scores),
+            // shown only on the entropy calculation page
+            WordleWorkerOutput::Progress { .. } => (),
+            WordleWorkerOutput::Err(err) => set_toast(ToastOption::new(
+                format!("Worker error: {err}"),
+                ToastType::Error,
+            )),
+            _ => set_toast(ToastOption::new(
+                "Unexpected worker output".to_string(),
+                ToastType::Error,
+            )),
+        }
+    };
+
+    let worker = WordleWorkerAtom::with_callback(Rc::new(cb));
+
+    {
+        let worker = worker.clone();
+        let word_set = word_set.clone();
+        let word_set_name = word_set.name.clone();
+        let state = state.clone();
+        let boards = boards.clone();
+        use_effect_with_deps(
+            move |_| {
+                worker.send(WordleWorkerInput::SetWordSetEncoded(
+                    to_vec_packed(&word_set.reduce_entropies(10)).unwrap(),
+                ));
+                state.dispatch(MultiSolverAction::Reset(word_set.clone(), *boards));
+                || ()
+            },
+            word_set_name,
+        )
+    }
+
+    {
+        let worker = worker.clone();
+        let scores = scores.clone();
+        let solved = state.is_solved();
+        use_effect_with_deps(
+            move |(_, boards, guesses): &(String, usize, Vec<(usize, Vec<usize>)>)| {
+                scores.set(vec![]);
+                if !solved {
+                    worker.send(WordleWorkerInput::MultiBoard {
+                        boards: *boards,
+                        guesses: guesses.clone(),
+                    });
+                }
+                || ()
+            },
+            (
+                state.word_set_name.clone(),
+                state.boards.len(),
+                state.history.clone(),
+            ),
+        )
+    }
+
+    let on_boards_change = {
+        let boards = boards.clone();
+        let state = state.clone();
+        let word_set = word_set.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(new_boards) = select.value().parse::<usize>() {
+                boards.set(new_boards);
+                state.dispatch(MultiSolverAction::Reset(word_set.clone(), new_boards));
+            }
+        })
+    };
+
+    let oninput = {
+        let word_set = word_set.clone();
+        let state = state.clone();
+
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            state.dispatch(MultiSolverAction::NewWord(input.value(), word_set.clone()));
+        })
+    };
+
+    let onclick_hints = |board: usize| {
+        let state = state.clone();
+        let word_set = word_set.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            let element: HtmlElement = e.target_unchecked_into();
+            if let Some(i) = element
+                .dataset()
+                .get("i")
+                .and_then(|i| i.parse::<usize>().ok())
+            {
+                state.dispatch(MultiSolverAction::ToggleHint(board, i, word_set.clone()));
+            }
+        })
+    };
+
+    let onclick_enter = {
+        let state = state.clone();
+        let word_set = word_set.clone();
+
+        Callback::from(move |_| state.dispatch(MultiSolverAction::Enter(word_set.clone())))
+    };
+
+    let onclick_restart = {
+        let state = state.clone();
+        let word_set = word_set.clone();
+        let boards = boards.clone();
+
+        Callback::from(move |_| state.dispatch(MultiSolverAction::Reset(word_set.clone(), *boards)))
+    };
+
+    let onclick_suggestion = {
+        let word_set = word_set.clone();
+        let state = state.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            let element: HtmlElement = e.target_unchecked_into();
+            if let Some(word) = element.dataset().get("word") {
+                state.dispatch(MultiSolverAction::NewWord(word, word_set.clone()));
+            }
+        })
+    };
+
+    let dictionary = &word_set.dictionary;
+
+    html! {
+        <section>
+            <div class="container pb-2">
+                <div class="columns">
+                    <div class="column col-2 col-xl-4 col-sm-6 col-xs-8 col-mx-auto text-center">
+                        <div class="form-group">
+                            <label class="form-label" for="boards">{ "Boards" }</label>
+                            <select class="form-select" id="boards" onchange={on_boards_change}>
+                                {
+                                    BOARD_COUNTS.iter().map(|&count| html! {
+                                        <option value={count.to_string()} selected={count == *boards}>
+                                            { count }
+                                        </option>
+                                    }).collect::<Html>()
+                                }
+                            </select>
+                        </div>
+                        <div class={classes!("form-group", state.error.as_ref().map(|_| "has-error"))}>
+                            <label class="form-label">
+                            { "Next guess" }
+                            </label>
+                            <input type="text" placeholder={state.word.to_string()} {oninput} />
+                            if let Some(ref err) = state.error {
+                                <p class="form-input-hint">{ err }</p>
+                            }
+                        </div>
+                        <div class="form-group">
+                            <label class="form-label">
+                            { "Hints of each board (click each block to change)" }
+                            </label>
+                            {
+                                state.boards.iter().zip(state.hints.iter()).enumerate().map(|(i, (board, hints))| {
+                                    if board.solved {
+                                        html! { <p class="text-success">{ format!("Board {} solved", i + 1) }</p> }
+                                    } else {
+                                        html! {
+                                            <div onclick={onclick_hints(i)} class="c-hand">
+                                                <HintedWord word={state.word.clone()} hints={hints.clone()} />
+                                                <small class="text-gray">
+                                                    { format!("{} answers left", board.answers) }
+                                                </small>
+                                            </div>
+                                        }
+                                    }
+                                }).collect::<Html>()
+                            }
+                        </div>
+                        <button class="btn btn-primary mx-1" onclick={onclick_enter} disabled={state.is_solved()}>
+                            { "Enter" }
+                        </button>
+                        <button class="btn btn-primary mx-1" onclick={onclick_restart}>{ "Restart" }</button>
+                    </div>
+                </div>
+            </div>
+            <div class="container">
+                <div class="columns">
+                    <div class="column col-6 col-md-12">
+                        <h5>{ "Guesses" }</h5>
+                        {
+                            state.history.iter().map(|(guess, hints)| html! {
+                                <div class="pb-2">
+                                    {
+                                        hints.iter().map(|&hints| html! {
+                                            <div>
+                                                <HintedWord word={dictionary.word(*guess)} hints={dictionary.hints(hints)} />
+                                            </div>
+                                        }).collect::<Html>()
+                                    }
+                                </div>
+                            }).collect::<Html>()
+                        }
+                    </div>
+                    <div class="column col-6 col-md-12" onclick={onclick_suggestion}>
+                        <h5>{ "Suggestions" }</h5>
+                        <table class="table">
+                            <thead>
+                                <tr>
+                                    <th>{ "Word" }</th>
+                                    <th>{ "Entropy" }</th>
+                                    <th>{ "Boards solved" }</th>
+                                    <th>{ "Score" }</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {
+                                    scores.iter().map(|score| {
+                                        let word = dictionary.word(score.guess);
+                                        html! {
+                                            <tr>
+                                                <td class="c-hand text-primary" data-word={word.clone()}>{ word }</td>
+                                                <td>{ format!("{:.3}", score.entropy) }</td>
+                                                <td>{ format!("{:.3}", score.solve_probability) }</td>
+                                                <td>{ format!("{:.3}", score.score) }</td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </tbody>
+                        </table>
+                    </div>
+                </div>
+            </div>
+        </section>
+    }
+}
@@ -7,10 +7,12 @@ use rand::{seq::IteratorRandom, seq::SliceRandom, thread_rng};
 use serde_cbor::ser::to_vec_packed;
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
-use web_sys::HtmlElement;
+use web_sys::{HtmlElement, HtmlInputElement};
+use wordle_entropy_core::strategy::StrategyKind;
+use wordle_entropy_core::structs::EntropiesData;
 use yew::{
     classes, function_component, html, use_effect_with_deps, use_mut_ref, use_reducer, use_state,
-    use_state_eq, Callback, Html, MouseEvent, Reducible, TargetCast,
+    use_state_eq, Callback, Event, Html, MouseEvent, Reducible, TargetCast,
 };
 use yew_router::history::{History, Location};
 use yew_router::hooks::use_history;
@@ -22,9 +24,9 @@ use crate::components::{
 use crate::simulation::{SimulationInput, SimulationOutput};
 use crate::util::scores_without_full_data;
 use crate::word_set::{get_current_word_set, WordSet};
+use crate::words::correct_hints;
 use crate::worker::{WordleWorkerInput, WordleWorkerOutput};
 use crate::worker_atom::WordleWorkerAtom;
-use crate::{EntropiesData, Hints};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct GuessStep {
@@ -38,7 +40,8 @@ pub struct GuessStep {
 
 enum SimulationStateAction {
     Reset,
-    Initialize(usize, Vec<usize>, Rc<WordSet>),
+    /// The current word is unknown in the adversarial mode
+    Initialize(Option<usize>, Vec<usize>, Rc<WordSet>),
     NextStep {
         next_word: Option<usize>,
         ended: bool,
@@ -71,7 +74,7 @@ impl Reducible for SimulationState {
             SimulationStateAction::Initialize(word, words_left, word_set) => Rc::new(Self {
                 current_turns: vec![],
                 turns_data: vec![],
-                current_word: Some(word),
+                current_word: word,
                 history: VecDeque::new(),
                 history_small: vec![],
                 words_left,
@@ -93,7 +96,7 @@ impl Reducible for SimulationState {
                 let mut history = self.history.clone();
                 let mut history_small = self.history_small.clone();
 
-                if let None = self.current_word {
+                if !self.running {
                     return self.clone();
                 }
 
@@ -119,12 +122,18 @@ impl Reducible for SimulationState {
 
                 let word = if answers.len() == 1 {
                     let turns_num = current_turns.len();
+                    let dictionary = &self
+                        .word_set
+                        .as_ref()
+                        .expect("Word set not available")
+                        .dictionary;
 
                     let answer = answers[0];
+                    history_front.0 = answer;
                     if answer != guess {
                         history_front.1.push(GuessStep {
                             guess: answer,
-                            hints: Hints::correct().to_ind(),
+                            hints: correct_hints(dictionary.word_length()),
                             uncertainty: 0.,
                             scores,
                             answers: vec![answer],
@@ -140,11 +149,7 @@ impl Reducible for SimulationState {
                                 (
                                     uncertainty,
                                     turns_num as f64 - turn - 1., // that additional one is already in the score
-                                    self.word_set // as we're interested only in failed guesses
-                                        .as_ref()
-                                        .expect("Word set not available")
-                                        .dictionary
-                                        .probabilities[guess],
+                                    dictionary.probabilities()[guess], // as we're interested only in failed guesses
                                 )
                             })
                             .filter(|&(_, turn, _)| turn > 0.),
@@ -191,6 +196,9 @@ pub enum Tab {
 pub fn view() -> Html {
     let word_set = Rc::new(get_current_word_set());
     let selected_words = use_mut_ref(|| SelectedWords::Random(10));
+    let hard_mode = use_mut_ref(|| false);
+    let strategy = use_mut_ref(StrategyKind::default);
+    let adversarial = use_mut_ref(|| false);
 
     let simulation_state = use_reducer(|| SimulationState::default());
     let stepping = use_mut_ref(|| false);
@@ -223,10 +231,38 @@ pub fn view() -> Html {
         })
     };
 
+    let on_hard_mode_change = {
+        let hard_mode = hard_mode.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            *hard_mode.borrow_mut() = input.checked();
+        })
+    };
+
+    let on_adversarial_change = {
+        let adversarial = adversarial.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            *adversarial.borrow_mut() = input.checked();
+        })
+    };
+
+    let on_strategy_change = {
+        let strategy = strategy.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(new_strategy) = StrategyKind::from_str(&select.value()) {
+                *strategy.borrow_mut() = new_strategy;
+            }
+        })
+    };
+
     let cb = {
         let send_queue = send_queue.clone();
         let simulation_state = simulation_state.clone();
         let words_left = words_left.clone();
+        let hard_mode = hard_mode.clone();
+        let strategy = strategy.clone();
         let set_toast = set_toast.clone();
 
         move |output: WordleWorkerOutput| match output {
@@ -246,19 +282,13 @@ pub fn view() -> Html {
                     answers,
                     ..
                 } => {
-                    let next_guess = scores
-                        .iter()
-                        .filter(|&&(candidate, ..)| candidate != guess)
-                        .next()
-                        .unwrap()
-                        .0;
-
                     let (next_word, ended) = if answers.len() > 1 {
                         match send_queue.try_borrow_mut() {
                             Ok(ref mut send_queue) => {
+                                // the worker picks the next guess with the chosen strategy
                                 **send_queue = Some(SimulationInput::Continue {
                                     hints: None,
-                                    guess: Some(next_guess),
+                                    guess: None,
                                 });
                             }
                             _ => log::error!("Unable to borrow in worker callback 1"),
@@ -272,6 +302,8 @@ pub fn view() -> Html {
                                     **send_queue = Some(SimulationInput::StartKnownAnswer {
                                         correct: next_word.clone(),
                                         guess: None,
+                                        hard_mode: *hard_mode.borrow(),
+                                        strategy: *strategy.borrow(),
                                     });
                                 }
                                 _ => log::error!("Unable to borrow in worker callback 2"),
@@ -294,6 +326,8 @@ pub fn view() -> Html {
                 }
                 SimulationOutput::Stopped => todo!(),
             },
+            // shown only on the entropy calculation page
+            WordleWorkerOutput::Progress { .. } => (),
             WordleWorkerOutput::Err(err) => {
                 simulation_state.dispatch(SimulationStateAction::Reset);
                 set_toast(ToastOption::new(
@@ -349,14 +383,39 @@ pub fn view() -> Html {
         let word_set = word_set.clone();
         let all_words = all_words.clone();
         let set_toast = set_toast.clone();
+        let hard_mode = hard_mode.clone();
+        let strategy = strategy.clone();
+        let adversarial = adversarial.clone();
 
         Callback::from(move |_| {
             *stepping.borrow_mut() = false;
+            if *adversarial.borrow() {
+                // a single game, the answer is only known at the end
+                *all_words.borrow_mut() = vec![];
+                simulation_state.dispatch(SimulationStateAction::Initialize(
+                    None,
+                    vec![],
+                    word_set.clone(),
+                ));
+                worker.send(WordleWorkerInput::Simulation(
+                    SimulationInput::StartAdversarial {
+                        guess: None,
+                        hard_mode: *hard_mode.borrow(),
+                        strategy: *strategy.borrow(),
+                    },
+                ));
+                return;
+            }
+
             let mut words = match *selected_words.borrow() {
                 SelectedWords::Random(n) => {
                     let mut rng = thread_rng();
-                    let mut words_selected =
-                        (0..word_set.dictionary.words.len()).choose_multiple(&mut rng, n);
+                    let mut words_selected = word_set
+                        .dictionary
+                        .answers()
+                        .iter()
+                        .copied()
+                        .choose_multiple(&mut rng, n);
                     words_selected.shuffle(&mut rng);
                     words_selected
                 }
@@ -368,7 +427,7 @@ pub fn view() -> Html {
             if words.len() > 0 {
                 let word = words.remove(0);
                 simulation_state.dispatch(SimulationStateAction::Initialize(
-                    word.clone(),
+                    Some(word),
                     words,
                     word_set.clone(),
                 ));
@@ -376,6 +435,8 @@ pub fn view() -> Html {
                     SimulationInput::StartKnownAnswer {
                         correct: word,
                         guess: None,
+                        hard_mode: *hard_mode.borrow(),
+                        strategy: *strategy.borrow(),
                     },
                 ));
             } else {
@@ -440,6 +501,30 @@ pub fn view() -> Html {
             <div class="columns">
                 <div class="column col-2 col-xl-8 col-sm-12 col-mx-auto text-center">
                     <SelectWords dictionary={word_set.dictionary.clone()} {on_words_set} />
+                    <div class="form-group">
+                        <label class="form-switch">
+                            <input type="checkbox" onchange={on_hard_mode_change} checked={*hard_mode.borrow()} />
+                            <i class="form-icon"></i> { "Hard mode" }
+                        </label>
+                    </div>
+                    <div class="form-group">
+                        <label class="form-switch">
+                            <input type="checkbox" onchange={on_adversarial_change} checked={*adversarial.borrow()} />
+                            <i class="form-icon"></i> { "Adversarial" }
+                        </label>
+                    </div>
+                    <div class="form-group">
+                        <label class="form-label" for="strategy">{ "Strategy" }</label>
+                        <select class="form-select" id="strategy" onchange={on_strategy_change}>
+                            {
+                                StrategyKind::ALL.into_iter().map(|kind| html! {
+                                    <option value={kind.name()} selected={kind == *strategy.borrow()}>
+                                        { kind.name() }
+                                    </option>
+                                }).collect::<Html>()
+                            }
+                        </select>
+                    </div>
                     <button
                         class="btn btn-primary"
                         onclick={on_start_button_click}
@@ -485,7 +570,7 @@ pub fn view() -> Html {
                         <Calibration
                             data={simulation_state.turns_data.clone()}
                             word_set_name={word_set.name.clone()}
-                            used_calibration={word_set.calibration.get_calibration()} />
+                            used_calibration={word_set.calibration.get_model()} />
                         }
                     }
                 }
@@ -1,37 +1,26 @@
 use std::{collections::VecDeque, rc::Rc};
 
-use anyhow::{anyhow, Result};
 use bounce::use_atom_setter;
 use itertools::izip;
 use serde_cbor::ser::to_vec_packed;
 use web_sys::{HtmlElement, HtmlInputElement};
-use wordle_entropy_core::algo::get_valid_hints;
 use wordle_entropy_core::structs::hints::{Hint, ValidHints};
-use wordle_entropy_core::structs::HintsN;
+use wordle_entropy_core::structs::EntropiesData;
 use yew::{
-    classes, function_component, html, use_effect_with_deps, use_reducer, Callback, InputEvent,
-    MouseEvent, Reducible, TargetCast,
+    classes, function_component, html, use_effect_with_deps, use_reducer, use_state_eq, Callback,
+    Event, InputEvent, MouseEvent, Reducible, TargetCast,
 };
 
 use crate::components::{HintedWord, SimulationDetail, ToastOption, ToastType};
 use crate::simulation::{SimulationInput, SimulationOutput};
 use crate::util::scores_without_full_data;
 use crate::word_set::{get_current_word_set, WordSet};
+use crate::words::{hints_ind, parse_word, valid_hints_after};
 use crate::worker::{WordleWorkerInput, WordleWorkerOutput};
 use crate::worker_atom::WordleWorkerAtom;
-use crate::{EntropiesData, Hints, Knowledge, Word, WORD_SIZE};
 
 use super::GuessStep;
 
-fn parse_word(word: &str, words: &Vec<Word>) -> Result<(usize, Word)> {
-    let word = Word::try_from(word)?;
-    let i = words
-        .iter()
-        .position(|w| w == &word)
-        .ok_or_else(|| anyhow!("Word {word} not found in the current word set!"))?;
-    Ok((i, word))
-}
-
 enum SolverStateAction {
     NextStep {
         guess: usize,
@@ -39,14 +28,22 @@ enum SolverStateAction {
         uncertainty: f64,
         scores: Vec<(usize, EntropiesData, f64)>,
         answers: Vec<usize>,
-        knowledge: Knowledge,
     },
 }
 
 #[derive(Clone, Default, PartialEq)]
 struct SolverState {
     history: VecDeque<(usize, Vec<GuessStep>)>,
-    knowledge: Knowledge,
+}
+
+impl SolverState {
+    /// Guesses of the current game with their hints
+    fn steps(&self) -> Vec<(usize, usize)> {
+        self.history
+            .front()
+            .map(|(_, steps)| steps.iter().map(|step| (step.guess, step.hints)).collect())
+            .unwrap_or_default()
+    }
 }
 
 impl Reducible for SolverState {
@@ -60,7 +57,6 @@ impl Reducible for SolverState {
                 uncertainty,
                 scores,
                 answers,
-                knowledge,
             } => {
                 let mut history = self.history.clone();
 
@@ -83,44 +79,47 @@ impl Reducible for SolverState {
                     answers: answers.clone(),
                 });
 
-                Rc::new(Self { history, knowledge })
+                Rc::new(Self { history })
             }
         }
     }
 }
 
+/// Both with the guesses of the current game and their hints
 enum WordStateAction {
-    NewWord(String, Rc<WordSet>, Knowledge),
-    ToggleHint(usize, Knowledge),
+    NewWord(String, Rc<WordSet>, Vec<(usize, usize)>),
+    ToggleHint(usize, Rc<WordSet>, Vec<(usize, usize)>),
 }
 
 #[derive(Clone, PartialEq)]
 struct WordState {
     word_ind: Option<usize>,
-    word: Word,
-    hints: Hints,
+    word: String,
+    hints: Vec<Hint>,
     valid_hints: ValidHints,
     error: Option<String>,
 }
 
 impl WordState {
     pub fn initialize(word_set: &WordSet) -> WordState {
+        let dictionary = &word_set.dictionary;
+        let word_length = dictionary.word_length();
         let (word_ind, word) = if let Some(&word) = word_set
             .entropies
             .as_ref()
             .and_then(|entropies| entropies.first().map(|(word, _, _)| word))
         {
-            (Some(word), word_set.dictionary.words[word].clone())
-        } else if let Some(word) = word_set.dictionary.words.first() {
-            (Some(0), word.clone())
+            (Some(word), dictionary.word(word))
+        } else if dictionary.words_len() > 0 {
+            (Some(0), dictionary.word(0))
         } else {
-            (None, Word::try_from("     ").ok().unwrap())
+            (None, " ".repeat(word_length))
         };
         WordState {
             word_ind,
             word,
-            hints: Hints::wrong(),
-            valid_hints: ValidHints::any(WORD_SIZE),
+            hints: vec![Hint::Wrong; word_length],
+            valid_hints: ValidHints::any(word_length),
             error: None,
         }
     }
@@ -138,54 +137,53 @@ impl Reducible for WordState {
             mut error,
         } = (*self).clone();
         match action {
-            WordStateAction::NewWord(new_word, word_set, knowledge) => {
-                match parse_word(new_word.as_str(), &word_set.dictionary.words) {
-                    Ok((i, new_word)) => {
+            WordStateAction::NewWord(new_word, word_set, steps) => {
+                match parse_word(new_word.as_str(), &word_set.dictionary) {
+                    Ok(i) => {
+                        let new_word = word_set.dictionary.word(i);
                         let same_chars = word
-                            .0
-                            .iter()
-                            .zip(new_word.0.iter())
+                            .chars()
+                            .zip(new_word.chars())
                             .map(|(o, n)| o == n)
                             .collect::<Vec<_>>();
 
                         word_ind = Some(i);
                         word = new_word;
                         error = None;
-                        valid_hints = get_valid_hints(&word, &self.hints, &knowledge);
-
-                        hints = HintsN::<WORD_SIZE>(
-                            izip!(&hints.0, &valid_hints.0, same_chars)
-                                .map(|(h, valid, same)| {
-                                    if valid.contains(h) && same {
-                                        *h
-                                    } else {
-                                        valid.first().copied().unwrap_or(Hint::Wrong)
-                                    }
-                                })
-                                .collect::<Vec<_>>()
-                                .try_into()
-                                .unwrap(),
-                        )
+                        valid_hints =
+                            valid_hints_after(&word_set.dictionary, &steps, i, &self.hints);
+
+                        hints = izip!(&hints, &valid_hints.0, same_chars)
+                            .map(|(h, valid, same)| {
+                                if valid.contains(h) && same {
+                                    *h
+                                } else {
+                                    valid.first().copied().unwrap_or(Hint::Wrong)
+                                }
+                            })
+                            .collect();
                     }
                     Err(err) => {
                         error = Some(err.to_string());
                     }
                 }
             }
-            WordStateAction::ToggleHint(i, knowledge) => {
-                let old_hint = hints.0[i];
+            WordStateAction::ToggleHint(i, word_set, steps) => {
+                let old_hint = hints[i];
                 let valid = &valid_hints.0[i];
                 let hint_pos = valid.iter().position(|&x| x == old_hint).unwrap_or(0);
 
                 let valid_hints_len = valid.len();
 
-                hints.0[i] = if valid_hints_len > 0 {
+                hints[i] = if valid_hints_len > 0 {
                     valid[(hint_pos + 1) % valid_hints_len]
                 } else {
                     Hint::Wrong
                 };
 
-                valid_hints = get_valid_hints(&self.word, &self.hints, &knowledge);
+                if let Some(word_ind) = word_ind {
+                    valid_hints = valid_hints_after(&word_set.dictionary, &steps, word_ind, &hints);
+                }
             }
         }
 
@@ -245,6 +243,7 @@ pub fn view() -> Html {
     let word_set = Rc::new(get_current_word_set());
     let set_toast = use_atom_setter::<ToastOption>();
     let common_state = use_reducer(|| CommonState::initialize(&*word_set));
+    let hard_mode = use_state_eq(|| false);
 
     let cb = {
         let set_toast = set_toast.clone();
@@ -259,7 +258,6 @@ pub fn view() -> Html {
                     uncertainty,
                     scores,
                     answers,
-                    knowledge,
                 } => {
                     common_state.dispatch(CommonStateAction::Solver(SolverStateAction::NextStep {
                         guess,
@@ -267,7 +265,6 @@ pub fn view() -> Html {
                         uncertainty,
                         scores,
                         answers,
-                        knowledge,
                     }))
                 }
                 _ => set_toast(ToastOption::new(
@@ -275,6 +272,12 @@ pub fn view() -> Html {
                     ToastType::Error,
                 )),
             },
+            // shown only on the entropy calculation page
+            WordleWorkerOutput::Progress { .. } => (),
+            WordleWorkerOutput::Err(err) => set_toast(ToastOption::new(
+                format!("Worker error: {err}"),
+                ToastType::Error,
+            )),
             _ => set_toast(ToastOption::new(
                 "Unexpected worker output".to_string(),
                 ToastType::Error,
@@ -300,6 +303,7 @@ pub fn view() -> Html {
     }
 
     let onclick_hints = {
+        let word_set = word_set.clone();
         let common_state = common_state.clone();
 
         Callback::from(move |e: MouseEvent| {
@@ -311,7 +315,8 @@ pub fn view() -> Html {
             {
                 common_state.dispatch(CommonStateAction::Word(WordStateAction::ToggleHint(
                     i,
-                    common_state.solver_state.knowledge.clone(),
+                    word_set.clone(),
+                    common_state.solver_state.steps(),
                 )));
             }
         })
@@ -326,28 +331,38 @@ pub fn view() -> Html {
             common_state.dispatch(CommonStateAction::Word(WordStateAction::NewWord(
                 input.value().clone(),
                 word_set.clone(),
-                common_state.solver_state.knowledge.clone(),
+                common_state.solver_state.steps(),
             )));
         })
     };
 
+    let on_hard_mode_change = {
+        let hard_mode = hard_mode.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            hard_mode.set(input.checked());
+        })
+    };
+
     let onclick_enter = {
         let worker = worker.clone();
         let set_toast = set_toast.clone();
         let common_state = common_state.clone();
+        let hard_mode = hard_mode.clone();
 
         Callback::from(move |_| {
             if let Some(guess) = common_state.word_state.word_ind {
                 if common_state.solver_state.history.len() == 0 {
                     worker.send(WordleWorkerInput::Simulation(
                         SimulationInput::StartUnknownAnswer {
-                            hints: common_state.word_state.hints.to_ind(),
+                            hints: hints_ind(&common_state.word_state.hints),
                             guess: Some(guess),
+                            hard_mode: *hard_mode,
                         },
                     ))
                 } else {
                     worker.send(WordleWorkerInput::Simulation(SimulationInput::Continue {
-                        hints: Some(common_state.word_state.hints.to_ind()),
+                        hints: Some(hints_ind(&common_state.word_state.hints)),
                         guess: Some(guess),
                     }))
                 }
@@ -377,12 +392,23 @@ pub fn view() -> Html {
                 common_state.dispatch(CommonStateAction::Word(WordStateAction::NewWord(
                     word,
                     word_set.clone(),
-                    common_state.solver_state.knowledge.clone(),
+                    common_state.solver_state.steps(),
                 )));
             }
         })
     };
 
+    // the tree doesn't keep to the revealed hints, its guesses might not be allowed in hard mode
+    let strategy_guess = word_set
+        .strategy
+        .as_ref()
+        .filter(|_| !*hard_mode)
+        .and_then(|strategy| {
+            strategy
+                .follow(common_state.solver_state.steps())
+                .map(|node| word_set.dictionary.word(node.guess))
+        });
+
     html! {
         <section>
             <div class="container pb-2">
@@ -405,6 +431,23 @@ pub fn view() -> Html {
                                 <HintedWord word={common_state.word_state.word.clone()} hints={common_state.word_state.hints.clone()} />
                             </div>
                         </div>
+                        <div class="form-group">
+                            <label class="form-switch">
+                                <input
+                                    type="checkbox"
+                                    onchange={on_hard_mode_change}
+                                    checked={*hard_mode}
+                                    disabled={common_state.solver_state.history.len() > 0}
+                                />
+                                <i class="form-icon"></i> { "Hard mode" }
+                            </label>
+                        </div>
+                        if let Some(strategy_guess) = strategy_guess {
+                            <p onclick={onclick_suggestion.clone()}>
+                                { "Heuristic strategy: " }
+                                <span class="c-hand text-primary" data-word={strategy_guess.clone()}>{ strategy_guess }</span>
+                            </p>
+                        }
                         <button class="btn btn-primary mx-1" onclick={onclick_enter}>{ "Enter" }</button>
                         <button class="btn btn-primary mx-1" onclick={onclick_restart}>{ "Restart" }</button>
                     </div>
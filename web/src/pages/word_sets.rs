@@ -1,39 +1,48 @@
 use std::rc::Rc;
+use std::str::FromStr;
 
 use anyhow::{anyhow, Result};
-use bounce::{use_atom_setter, use_slice, use_slice_dispatch};
+use bounce::{use_atom_setter, use_slice, use_slice_dispatch, UseSliceHandle};
 use gloo_file::{
-    callbacks::{read_as_text, FileReader},
-    FileList, FileReadError,
+    callbacks::{read_as_bytes, read_as_text, FileReader},
+    Blob, FileList, FileReadError, ObjectUrl,
 };
+use gloo_timers::callback::Timeout;
+use gloo_utils::document;
+use wasm_bindgen::JsCast;
 use web_sys::{HtmlElement, HtmlInputElement};
-use wordle_entropy_core::{calibration::Calibration, data::parse_words};
+use wordle_entropy_core::data::{LoadOptions, LoadWarning};
+use wordle_entropy_core::priors::Prior;
 use yew::{
-    function_component, html, use_mut_ref, use_node_ref, Callback, FocusEvent, Html, MouseEvent,
-    TargetCast,
+    function_component, html, use_mut_ref, use_node_ref, use_state_eq, Callback, Event, FocusEvent,
+    Html, MouseEvent, TargetCast,
 };
 
 use crate::{
     components::{ToastOption, ToastType},
-    word_set::{SetCalibration, WordSetVec, WordSetVecAction},
-    WORD_SIZE,
+    word_set::{parse_dictionary, SetCalibration, WordSet, WordSetVec, WordSetVecAction},
 };
 
+/// File extension of the exported word sets
+const EXPORT_EXTENSION: &str = "wordset";
+
 fn handle_file(
     name: String,
     content: std::result::Result<String, FileReadError>,
+    prior: Prior,
     dispatch_word_set: Rc<dyn Fn(WordSetVecAction)>,
-) -> Result<()> {
+) -> Result<Vec<LoadWarning>> {
     let content = content?;
-    let dictionary = parse_words::<_, WORD_SIZE>(content.lines())?;
+    let (dictionary, warnings) = parse_dictionary(&content, &LoadOptions::default())?;
 
-    dispatch_word_set(WordSetVecAction::LoadWords(name, dictionary));
-    Ok(())
+    dispatch_word_set(WordSetVecAction::LoadWords(name, dictionary, prior));
+    Ok(warnings)
 }
 
 fn load_from_file(
     name: String,
     files: Option<FileList>,
+    prior: Prior,
     dispatch_word_set: Rc<dyn Fn(WordSetVecAction)>,
     set_toast: Rc<dyn Fn(ToastOption)>,
 ) -> Result<FileReader> {
@@ -45,8 +54,15 @@ fn load_from_file(
     let file = files.first().ok_or(anyhow!("No file selected!"))?;
 
     Ok(read_as_text(&file, move |res| {
-        match handle_file(name, res, dispatch_word_set) {
-            Ok(_) => (),
+        match handle_file(name, res, prior, dispatch_word_set) {
+            Ok(warnings) => {
+                if let Some(warning) = warnings.first() {
+                    set_toast(ToastOption::new(
+                        format!("{} lines skipped, e.g. {warning}", warnings.len()),
+                        ToastType::Warning,
+                    ))
+                }
+            }
             Err(err) => set_toast(ToastOption::new(
                 format!("Reading file error: {err}").to_string(),
                 ToastType::Error,
@@ -55,11 +71,118 @@ fn load_from_file(
     }))
 }
 
+fn handle_import(
+    content: std::result::Result<Vec<u8>, FileReadError>,
+    word_sets: &WordSetVec,
+) -> Result<WordSet> {
+    let word_set = WordSet::import(&content?)?;
+    if word_sets.0.iter().any(|other| other.name == word_set.name) {
+        return Err(anyhow!(
+            "Word set \"{}\" already exists, remove it first",
+            word_set.name
+        ));
+    }
+
+    Ok(word_set)
+}
+
+fn import_from_file(
+    files: Option<FileList>,
+    word_sets: UseSliceHandle<WordSetVec>,
+    set_toast: Rc<dyn Fn(ToastOption)>,
+) -> Result<FileReader> {
+    let files = files.ok_or(anyhow!("No file selected!"))?;
+    let file = files.first().ok_or(anyhow!("No file selected!"))?;
+
+    Ok(read_as_bytes(&file, move |res| {
+        match handle_import(res, &word_sets) {
+            Ok(word_set) => word_sets.dispatch(WordSetVecAction::Add(word_set)),
+            Err(err) => set_toast(ToastOption::new(
+                format!("Importing file error: {err}"),
+                ToastType::Error,
+            )),
+        }
+    }))
+}
+
+/// How long the URL of an exported word set stays valid after starting the download
+const DOWNLOAD_URL_LIFETIME_MS: u32 = 60_000;
+
+/// Lets the browser save the exported word set as a file
+fn download_word_set(word_set: &WordSet, include_computed: bool) -> Result<()> {
+    let bytes = word_set.export(include_computed)?;
+    let url = ObjectUrl::from(Blob::new_with_options(&bytes[..], Some("application/cbor")));
+
+    let link = document()
+        .create_element("a")
+        .and_then(|element| {
+            element.set_attribute("href", &url)?;
+            element.set_attribute("download", &format!("{}.{EXPORT_EXTENSION}", word_set.name))?;
+            Ok(element)
+        })
+        .map_err(|err| anyhow!("Unable to create the download link: {err:?}"))?
+        .unchecked_into::<HtmlElement>();
+    link.click();
+    // the browser may only start reading the blob after the click,
+    // so the URL is revoked once the download had the time to start
+    Timeout::new(DOWNLOAD_URL_LIFETIME_MS, move || drop(url)).forget();
+
+    Ok(())
+}
+
+#[function_component(ImportWordSetForm)]
+pub fn import_form() -> Html {
+    let word_sets = use_slice::<WordSetVec>();
+    let file_input_node_ref = use_node_ref();
+    let file_reader = use_mut_ref(|| None);
+    let set_toast = use_atom_setter::<ToastOption>();
+
+    let onimport = {
+        let file_input_node_ref = file_input_node_ref.clone();
+
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+            let file_input = file_input_node_ref.cast::<HtmlInputElement>().unwrap();
+            let files = file_input
+                .files()
+                .map(|files| gloo_file::FileList::from(files));
+
+            match import_from_file(files, word_sets.clone(), set_toast.clone()) {
+                Ok(loaded_file_reader) => *file_reader.borrow_mut() = Some(loaded_file_reader),
+                Err(err) => set_toast(ToastOption::new(
+                    format!("Importing file error: {err}"),
+                    ToastType::Error,
+                )),
+            }
+        })
+    };
+
+    html! {
+        <form class="form-horizontal" onsubmit={onimport}>
+            <div class="form-group">
+                <div class="col-3">
+                    <label class="form-label" for="import_input">{ "Word set file" }</label>
+                </div>
+                <div class="col-9">
+                    <input id="import_input" class="form-input" ref={file_input_node_ref} type="file"/>
+                </div>
+            </div>
+            <div class="form-group">
+                <div class="col-8" />
+                <div class="col-4">
+                    <button class="btn btn-primary">{"Import word set"}</button>
+                </div>
+            </div>
+        </form>
+    }
+}
+
 #[function_component(AddWordSetForm)]
 pub fn form() -> Html {
     let dispatch_word_set = use_slice_dispatch::<WordSetVec>();
     let file_input_node_ref = use_node_ref();
     let name_input_node_ref = use_node_ref();
+    let prior_select_node_ref = use_node_ref();
     let file_reader = use_mut_ref(|| None);
     let set_toast = use_atom_setter::<ToastOption>();
 
@@ -67,6 +190,7 @@ pub fn form() -> Html {
         let file_reader = file_reader.clone();
         let file_input_node_ref = file_input_node_ref.clone();
         let name_input_node_ref = name_input_node_ref.clone();
+        let prior_select_node_ref = prior_select_node_ref.clone();
         let dispatch_word_set = dispatch_word_set.clone();
         let set_toast = set_toast.clone();
 
@@ -82,7 +206,16 @@ pub fn form() -> Html {
                 .files()
                 .map(|files| gloo_file::FileList::from(files));
 
-            match load_from_file(name, files, dispatch_word_set, set_toast.clone()) {
+            let prior_select = prior_select_node_ref.cast::<HtmlInputElement>().unwrap();
+            let prior = match Prior::from_str(&prior_select.value()) {
+                Ok(prior) => prior,
+                Err(err) => {
+                    set_toast(ToastOption::new(err.to_string(), ToastType::Error));
+                    return;
+                }
+            };
+
+            match load_from_file(name, files, prior, dispatch_word_set, set_toast.clone()) {
                 Ok(loaded_file_reader) => *file_reader.borrow_mut() = Some(loaded_file_reader),
                 Err(err) => set_toast(ToastOption::new(
                     format!("Reading file error: {err}").to_string(),
@@ -110,6 +243,22 @@ pub fn form() -> Html {
                     <input class="form-input" ref={file_input_node_ref} type="file"/>
                 </div>
             </div>
+            <div class="form-group">
+                <div class="col-3">
+                    <label class="form-label" for="prior_select">{ "Prior" }</label>
+                </div>
+                <div class="col-9">
+                    <select id="prior_select" class="form-select" ref={prior_select_node_ref}>
+                        {
+                            Prior::ALL.iter().map(|prior| html! {
+                                <option value={prior.to_string()} selected={*prior == Prior::default()}>
+                                    { prior.name() }
+                                </option>
+                            }).collect::<Html>()
+                        }
+                    </select>
+                </div>
+            </div>
             <div class="form-group">
                 <div class="col-8" />
                 <div class="col-4">
@@ -124,6 +273,36 @@ pub fn form() -> Html {
 pub fn view() -> Html {
     let word_sets = use_slice::<WordSetVec>();
     let dispatch_word_sets = use_slice_dispatch::<WordSetVec>();
+    let set_toast = use_atom_setter::<ToastOption>();
+    let include_computed = use_state_eq(|| false);
+
+    let onclick_export = {
+        let word_sets = word_sets.clone();
+        let include_computed = include_computed.clone();
+        Callback::from(move |e: MouseEvent| {
+            let element: HtmlElement = e.target_unchecked_into();
+            let word_set = element
+                .dataset()
+                .get("name")
+                .and_then(|name| word_sets.0.iter().find(|word_set| word_set.name == name));
+            if let Some(word_set) = word_set {
+                if let Err(err) = download_word_set(word_set, *include_computed) {
+                    set_toast(ToastOption::new(
+                        format!("Exporting error: {err}"),
+                        ToastType::Error,
+                    ));
+                }
+            }
+        })
+    };
+
+    let on_include_computed_change = {
+        let include_computed = include_computed.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            include_computed.set(input.checked());
+        })
+    };
 
     let onclick_remove = {
         let dispatch_word_sets = dispatch_word_sets.clone();
@@ -148,6 +327,19 @@ pub fn view() -> Html {
         })
     };
 
+    let on_prior_change = {
+        let dispatch_word_sets = dispatch_word_sets.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlInputElement = e.target_unchecked_into();
+            if let (Some(name), Ok(prior)) = (
+                select.dataset().get("name"),
+                Prior::from_str(&select.value()),
+            ) {
+                dispatch_word_sets(WordSetVecAction::SetPrior(name, prior));
+            }
+        })
+    };
+
     html! {
         <container>
             <h1>
@@ -157,9 +349,12 @@ pub fn view() -> Html {
                 <thead>
                     <tr>
                         <th>{ "Name" }</th>
+                        <th>{ "Word length" }</th>
                         <th>{ "# of words" }</th>
+                        <th>{ "# of answers" }</th>
                         <th>{ "Entropies" }</th>
                         <th>{ "Calibration" }</th>
+                        <th>{ "Prior" }</th>
                         <th></th>
                     </tr>
                 </thead>
@@ -171,7 +366,9 @@ pub fn view() -> Html {
                             html! {
                                 <tr key={name.clone()}>
                                     <td> {name.clone()} </td>
-                                    <td> {word_set.dictionary.words.len()} </td>
+                                    <td> {word_set.dictionary.word_length()} </td>
+                                    <td> {word_set.dictionary.words_len()} </td>
+                                    <td> {word_set.dictionary.answers().len()} </td>
                                     <td> {
                                         if let Some(_) = word_set.entropies {
                                             html! { <>{ "Available" }</> }
@@ -185,12 +382,11 @@ pub fn view() -> Html {
                                     }</td>
                                     <td>
                                     {
-                                        match word_set.calibration {
+                                        match &word_set.calibration {
                                             SetCalibration::Default => html! { <> { "Default" } </> },
-                                            SetCalibration::Custom(calibration) => {
-                                                let Calibration {c, a0, a1 } = calibration;
+                                            calibration => {
                                                 html! { <>
-                                                    { format!("Custom ({c:.3} ln({a0:.3} (x + {a1:.3})))") }
+                                                    { format!("Custom ({})", calibration.get_model()) }
                                                     <button onclick={onclick_reset_calibration.clone()} data-name={name.clone()} class="btn">{"Reset"} </button>
                                                     </> }
                                             }
@@ -198,6 +394,18 @@ pub fn view() -> Html {
                                     }
                                     </td>
                                     <td>
+                                        <select class="form-select" data-name={name.clone()} onchange={on_prior_change.clone()}>
+                                            {
+                                                Prior::ALL.iter().map(|prior| html! {
+                                                    <option value={prior.to_string()} selected={prior.name() == word_set.prior.name()}>
+                                                        { prior.name() }
+                                                    </option>
+                                                }).collect::<Html>()
+                                            }
+                                        </select>
+                                    </td>
+                                    <td>
+                                        <button onclick={onclick_export.clone()} class="btn" data-name={name.clone()}>{"Export"}</button>
                                         <button onclick={onclick_remove.clone()} class="btn" data-name={name.clone()}>{"Remove"}</button>
                                     </td>
                                 </tr>
@@ -206,10 +414,18 @@ pub fn view() -> Html {
                     }
                 </tbody>
             </table>
+            <div class="form-group">
+                <label class="form-checkbox">
+                    <input type="checkbox" checked={*include_computed} onchange={on_include_computed_change} />
+                    <i class="form-icon"></i>
+                    { "Export the computed entropies and strategy" }
+                </label>
+            </div>
             <div class="columns">
                 <div class="column col-4 col-xl-3 col-lg-2 col-md-0" />
                 <div class="column col-mx text-center">
                     <AddWordSetForm />
+                    <ImportWordSetForm />
                 </div>
                 <div class="column col-4 col-xl-3 col-lg-2 col-md-0" />
             </div>
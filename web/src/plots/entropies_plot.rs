@@ -4,9 +4,9 @@ use anyhow::{anyhow, Result};
 use plotters::{coord::ReverseCoordTranslate, prelude::*};
 use plotters_canvas::CanvasBackend;
 use web_sys::HtmlCanvasElement;
-use wordle_entropy_core::structs::hints::Hint;
+use wordle_entropy_core::structs::{hints::Hint, EntropiesData};
 
-use crate::{components::Plotter, word_set::WordSet, EntropiesData};
+use crate::{components::Plotter, word_set::WordSet};
 
 #[derive(Clone, PartialEq)]
 pub struct EntropiesPlotter {
@@ -85,13 +85,11 @@ impl Plotter for EntropiesPlotter {
 
         if let Some((x, y)) = xy {
             if let Some((hints, prob)) = indices.get(x).and_then(|&index| {
-                self.word_set.dictionary.hints.get(index).and_then(|hints| {
-                    self.entropies_data.as_ref().and_then(|entropies_data| {
-                        entropies_data
-                            .probabilities
-                            .get(index)
-                            .map(|&prob| (hints, prob))
-                    })
+                self.entropies_data.as_ref().and_then(|entropies_data| {
+                    entropies_data
+                        .probabilities
+                        .get(index)
+                        .map(|&prob| (self.word_set.dictionary.hints(index), prob))
                 })
             }) {
                 let size = 25;
@@ -102,13 +100,13 @@ impl Plotter for EntropiesPlotter {
                         + Rectangle::new(
                             [
                                 (0, -size - 10),
-                                (15 + padded_size * hints.0.len() as i32 + 5, 25),
+                                (15 + padded_size * hints.len() as i32 + 5, 25),
                             ],
                             WHITE.filled(),
                         ),
                 ))?;
 
-                for (i, h) in hints.0.into_iter().enumerate() {
+                for (i, h) in hints.into_iter().enumerate() {
                     let style = match h {
                         Hint::Wrong => RGBColor(58, 58, 60),
                         Hint::OutOfPlace => RGBColor(181, 159, 59),
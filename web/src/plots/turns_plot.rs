@@ -2,14 +2,14 @@ use anyhow::{anyhow, Result};
 use plotters::prelude::*;
 use plotters_canvas::CanvasBackend;
 use web_sys::HtmlCanvasElement;
-use wordle_entropy_core::calibration::{bounded_log_c, Calibration};
+use wordle_entropy_core::calibration::{bounded_turns, Model};
 
 use crate::components::Plotter;
 
 #[derive(Clone, PartialEq)]
 pub struct TurnsLeftPlotter {
-    pub calibration: Option<Calibration>,
-    pub used_calibration: Calibration,
+    pub calibration: Option<Model>,
+    pub used_calibration: Model,
     pub bar_per_1: i32,
     pub bar_data: Vec<((f64, f64), f64)>,
 }
@@ -68,13 +68,13 @@ impl Plotter for TurnsLeftPlotter {
         }))?;
 
         let axis_val_multiplier = 5.;
-        if let Some(calibration) = self.calibration {
+        if let Some(calibration) = &self.calibration {
             let color = &RED;
             chart
                 .draw_series(LineSeries::new(
                     (0..=((axis_val_multiplier * x_max.ceil()) as i32 + 1))
                         .map(|x| (x as f64) / axis_val_multiplier)
-                        .map(|x| (x, bounded_log_c(x, calibration))),
+                        .map(|x| (x, bounded_turns(x, calibration))),
                     color,
                 ))?
                 .label(format!("Fitted calibration"))
@@ -87,7 +87,7 @@ impl Plotter for TurnsLeftPlotter {
                 .draw_series(LineSeries::new(
                     (0..=((axis_val_multiplier * x_max.ceil()) as i32 + 1))
                         .map(|x| (x as f64) / axis_val_multiplier)
-                        .map(|x| (x, bounded_log_c(x, self.used_calibration))),
+                        .map(|x| (x, bounded_turns(x, &self.used_calibration))),
                     color,
                 ))?
                 .label(format!("Used calibration"))
@@ -1,23 +1,40 @@
 use std::rc::Rc;
+use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use wordle_entropy_core::{
-    algo::{get_answers, get_hints_and_update, update_knowledge},
-    entropy::{calculate_entropies, entropies_scored},
+    adversarial::{adversary_hints, adversary_hints_with_matrix},
+    algo::{filter_answers, get_hints, update_knowledge},
+    calibration::Model,
+    dispatch_dictionary,
+    entropy::{entropies_scored, guess_summaries_with_progress, uncertainty},
+    filter::{get_answers, get_guesses, KnowledgeFilter},
+    progress::{Cancelled, Progress},
+    strategy::{StrategyKind, Turn},
+    structs::{knowledge::KnowledgeN, AnyDictionary, Dictionary, EntropiesData, HintMatrix},
 };
 
-use crate::{word_set::WordSet, EntropiesData, Knowledge};
+use crate::word_set::WordSet;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub enum SimulationInput {
     StartKnownAnswer {
         correct: usize,
         guess: Option<usize>,
+        hard_mode: bool,
+        strategy: StrategyKind,
     },
     StartUnknownAnswer {
         hints: usize,
         guess: Option<usize>,
+        hard_mode: bool,
+    },
+    /// The answers shrink to the largest hints bucket after each guess
+    StartAdversarial {
+        guess: Option<usize>,
+        hard_mode: bool,
+        strategy: StrategyKind,
     },
     Continue {
         hints: Option<usize>,
@@ -34,163 +51,381 @@ pub enum SimulationOutput {
         uncertainty: f64,
         scores: Vec<(usize, EntropiesData, f64)>,
         answers: Vec<usize>,
-        knowledge: Knowledge,
     },
     Stopped,
 }
 
+/// Where the hints for the guesses come from
+#[derive(Clone, Copy)]
+pub enum Answer {
+    Known(usize),
+    /// The hints are passed along with each guess
+    Unknown,
+    /// The answers shrink to the largest hints bucket after each guess
+    Adversarial,
+}
+
+#[derive(Clone)]
 pub struct SimulationData {
-    word_set: Rc<WordSet>,
-    correct: Option<usize>,
-    knowledge: Knowledge,
-    entropies: Rc<Vec<(usize, EntropiesData, f64)>>,
+    answer: Answer,
+    hard_mode: bool,
+    strategy: StrategyKind,
+    /// Expected turns model of the word set
+    model: Model,
+    /// Guesses so far with their hints, as indices into the dictionary
+    history: Vec<(usize, usize)>,
     answers: Vec<usize>,
 }
 
 impl SimulationData {
-    fn new(word_set: &Rc<WordSet>, correct: Option<usize>) -> Result<Self> {
-        let entropies = word_set
-            .entropies
-            .as_ref()
-            .ok_or(anyhow!("Missing entropies"))?
-            .clone();
-
-        let answers = (0..word_set.dictionary.words.len()).collect::<Vec<_>>();
-
-        Ok(Self {
-            word_set: word_set.clone(),
-            correct,
-            knowledge: Knowledge::default(),
-            entropies,
-            answers,
+    fn new(word_set: &WordSet, answer: Answer, hard_mode: bool, strategy: StrategyKind) -> Self {
+        Self {
+            answer,
+            hard_mode,
+            strategy,
+            model: word_set.calibration.get_model(),
+            history: vec![],
+            answers: word_set.dictionary.answers().to_vec(),
+        }
+    }
+}
+
+/// Kept between simulations, as long as the dictionary stays the same
+pub struct SimulationCache {
+    /// Copy of the word set dictionary, so that the steps can be played on the thread pool
+    dictionary: Arc<AnyDictionary>,
+    /// Missing for the word sets too big to keep the hints of
+    matrix: Option<Arc<HintMatrix>>,
+    initial_entropies: Vec<(usize, EntropiesData)>,
+}
+
+impl SimulationCache {
+    /// The `matrix` is built with `build_matrix` if it's missing. The `progress` counts
+    /// its rows, then the initial entropies. Returns `None` if it was cancelled.
+    fn build(
+        dictionary: Arc<AnyDictionary>,
+        matrix: Option<Arc<HintMatrix>>,
+        build_matrix: bool,
+        progress: &Progress,
+    ) -> Option<Self> {
+        let (matrix, initial_entropies) = dispatch_dictionary!(&*dictionary, dictionary => {
+            let matrix = match matrix {
+                Some(matrix) => Some(matrix),
+                None if build_matrix => {
+                    Some(Arc::new(HintMatrix::with_progress(dictionary, progress).ok()?))
+                }
+                None => None,
+            };
+            let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+            let initial_entropies = guess_summaries_with_progress(
+                dictionary,
+                matrix.as_deref(),
+                &guesses,
+                &dictionary.answers,
+                progress,
+            )?;
+            (matrix, initial_entropies)
+        });
+
+        Some(Self {
+            dictionary,
+            matrix,
+            initial_entropies,
         })
     }
 }
 
+/// Cache of a [`SimulationJob`], built by the job when the dictionary changed
+enum JobCache {
+    Ready(Arc<SimulationCache>),
+    Build {
+        dictionary: Arc<AnyDictionary>,
+        matrix: Option<Arc<HintMatrix>>,
+        build_matrix: bool,
+    },
+}
+
+/// Next step of the simulation, played by [`SimulationJob::run`] outside of the worker
+pub struct SimulationJob {
+    cache: JobCache,
+    data: SimulationData,
+    hints: Option<usize>,
+    guess: Option<usize>,
+}
+
+impl SimulationJob {
+    /// Builds the cache if it's missing and plays the step.
+    /// Returns `None` if the `progress` was cancelled.
+    pub fn run(self, progress: &Progress) -> Option<SimulationStep> {
+        let cache = match self.cache {
+            JobCache::Ready(cache) => cache,
+            JobCache::Build {
+                dictionary,
+                matrix,
+                build_matrix,
+            } => Arc::new(SimulationCache::build(
+                dictionary,
+                matrix,
+                build_matrix,
+                progress,
+            )?),
+        };
+
+        let mut data = self.data;
+        let result = dispatch_dictionary!(&*cache.dictionary, dictionary => {
+            continue_simulation(dictionary, &cache, &mut data, self.hints, self.guess, progress)
+        });
+        if progress.is_cancelled() {
+            return None;
+        }
+
+        Some(SimulationStep {
+            cache,
+            result: result.map(|output| (data, output)),
+        })
+    }
+}
+
+/// Outcome of a [`SimulationJob`], kept by the simulation with [`Simulation::finish`]
+pub struct SimulationStep {
+    cache: Arc<SimulationCache>,
+    result: Result<(SimulationData, SimulationOutput)>,
+}
+
+impl SimulationStep {
+    /// Hint matrix the step was played with
+    pub fn matrix(&self) -> Option<Arc<HintMatrix>> {
+        self.cache.matrix.clone()
+    }
+}
+
 #[derive(Default)]
 pub struct Simulation {
     state: Option<SimulationData>,
+    /// Along with the word set dictionary it was built for
+    cache: Option<(Rc<AnyDictionary>, Arc<SimulationCache>)>,
 }
 
 impl Simulation {
+    /// Prepares the step asked for by the `input`, `None` if the simulation was stopped instead.
+    /// The `matrix` already built for the word set is used, a new one is built
+    /// with `build_matrix`.
     pub fn handle_message(
         &mut self,
-        word_set: &Rc<WordSet>,
+        word_set: &WordSet,
+        matrix: Option<Arc<HintMatrix>>,
+        build_matrix: bool,
         input: SimulationInput,
-    ) -> Result<SimulationOutput> {
-        match input {
-            SimulationInput::StartKnownAnswer { correct, guess } => {
-                self.handle_start(word_set, Some(correct), None, guess)
-            }
-            SimulationInput::StartUnknownAnswer { hints, guess } => {
-                self.handle_start(word_set, None, Some(hints), guess)
+    ) -> Result<Option<SimulationJob>> {
+        let start = |answer, hints, guess, hard_mode, strategy| {
+            self.handle_start(
+                word_set,
+                matrix,
+                build_matrix,
+                SimulationData::new(word_set, answer, hard_mode, strategy),
+                hints,
+                guess,
+            )
+        };
+        let job = match input {
+            SimulationInput::StartKnownAnswer {
+                correct,
+                guess,
+                hard_mode,
+                strategy,
+            } => start(Answer::Known(correct), None, guess, hard_mode, strategy),
+            SimulationInput::StartUnknownAnswer {
+                hints,
+                guess,
+                hard_mode,
+            } => start(
+                Answer::Unknown,
+                Some(hints),
+                guess,
+                hard_mode,
+                StrategyKind::default(),
+            ),
+            SimulationInput::StartAdversarial {
+                guess,
+                hard_mode,
+                strategy,
+            } => start(Answer::Adversarial, None, guess, hard_mode, strategy),
+            SimulationInput::Continue { hints, guess, .. } => self.handle_continue(hints, guess)?,
+            SimulationInput::Stop => {
+                self.state = None;
+                return Ok(None);
             }
-            SimulationInput::Continue { hints, guess, .. } => self.handle_continue(hints, guess),
-            SimulationInput::Stop => self.handle_stop(),
-        }
-    }
-
-    pub fn handle_start(
-        &mut self,
-        word_set: &Rc<WordSet>,
-        correct: Option<usize>,
-        hints: Option<usize>,
-        guess: Option<usize>,
-    ) -> Result<SimulationOutput> {
-        self.state = Some(SimulationData::new(word_set, correct)?);
+        };
 
-        self.handle_continue(hints, guess)
+        Ok(Some(job))
     }
 
-    pub fn handle_continue(
-        &mut self,
+    fn handle_start(
+        &self,
+        word_set: &WordSet,
+        matrix: Option<Arc<HintMatrix>>,
+        build_matrix: bool,
+        data: SimulationData,
         hints: Option<usize>,
         guess: Option<usize>,
-    ) -> Result<SimulationOutput> {
-        let data = self.state.as_mut().ok_or(anyhow!("Missing state"))?;
-
-        let guess = match guess {
-            Some(guess) => guess,
-            None => {
-                data.entropies
-                    .iter()
-                    .next()
-                    .ok_or(anyhow!(
-                        "Neither guess nor entropies available, unable to make the next guess!"
-                    ))?
-                    .0
+    ) -> SimulationJob {
+        let cache = match &self.cache {
+            Some((dictionary, cache)) if Rc::ptr_eq(dictionary, &word_set.dictionary) => {
+                JobCache::Ready(cache.clone())
             }
+            _ => JobCache::Build {
+                dictionary: Arc::new((*word_set.dictionary).clone()),
+                matrix,
+                build_matrix,
+            },
         };
 
-        let guess_word = &data.word_set.dictionary.words[guess];
-        let (hints, knowledge) = match (hints, data.correct) {
-            (Some(hints), None) => {
-                let guess = &data.word_set.dictionary.words[guess];
-                let hints_full = &data.word_set.dictionary.hints[hints];
-                let knowledge = update_knowledge(guess, hints_full, data.knowledge.clone());
-                (Some(hints), knowledge)
-            }
-            (None, Some(correct)) => {
-                let correct = &data.word_set.dictionary.words[correct];
-                let (hints, knowledge) =
-                    get_hints_and_update(guess_word, correct, data.knowledge.clone());
-                let hints = hints.to_ind();
-                (Some(hints), knowledge)
-            }
-            (Some(_), Some(_)) => {
-                return Err(anyhow!(
-                    "Tried to pass custom hints to a simulation that already has a known solution"
-                ))
-            }
-            (None, None) => {
-                return Err(anyhow!(
-                    "Didn't pass custom hints to a simulation that doesn't have a known solution"
-                ))
-            }
-        };
+        SimulationJob {
+            cache,
+            data,
+            hints,
+            guess,
+        }
+    }
 
-        data.answers = get_answers(data.word_set.dictionary.words.clone(), &knowledge);
-        data.knowledge = knowledge.clone();
-
-        let prob_norm: f64 = data
-            .answers
-            .iter()
-            .map(|&i| &data.word_set.dictionary.probabilities[i])
-            .sum();
-
-        let uncertainty = data
-            .answers
-            .iter()
-            .map(|&i| {
-                let probability: f64 = &data.word_set.dictionary.probabilities[i] / prob_norm;
-                -probability * probability.log2()
-            })
-            .sum();
-
-        let entropies = calculate_entropies(&data.word_set.dictionary, &data.answers[..]);
-        let scores = entropies_scored(
-            &data.word_set.dictionary,
-            &data.answers[..],
-            entropies,
-            Some(uncertainty),
-            Some(data.word_set.calibration.get_calibration()),
-        )
-        .into_iter()
-        .take(10)
-        .collect::<Vec<_>>();
+    fn handle_continue(&self, hints: Option<usize>, guess: Option<usize>) -> Result<SimulationJob> {
+        let data = self.state.clone().ok_or(anyhow!("Missing state"))?;
+        let (_, cache) = self.cache.as_ref().ok_or(anyhow!("Missing cache"))?;
 
-        Ok(SimulationOutput::StepComplete {
-            guess,
+        Ok(SimulationJob {
+            cache: JobCache::Ready(cache.clone()),
+            data,
             hints,
-            uncertainty,
-            scores,
-            answers: data.answers.clone(),
-            knowledge,
+            guess,
         })
     }
 
-    pub fn handle_stop(&mut self) -> Result<SimulationOutput> {
-        self.state = None;
-        Ok(SimulationOutput::Stopped)
+    /// Keeps the state after the `step`, along with its cache for the word set `dictionary`
+    pub fn finish(
+        &mut self,
+        dictionary: Rc<AnyDictionary>,
+        step: SimulationStep,
+    ) -> Result<SimulationOutput> {
+        self.cache = Some((dictionary, step.cache));
+        let (data, output) = step.result?;
+        self.state = Some(data);
+
+        Ok(output)
+    }
+}
+
+/// Plays the next turn of the simulation, with the `dictionary` of its word set
+fn continue_simulation<const N: usize>(
+    dictionary: &Dictionary<N>,
+    cache: &SimulationCache,
+    data: &mut SimulationData,
+    hints: Option<usize>,
+    guess: Option<usize>,
+    progress: &Progress,
+) -> Result<SimulationOutput> {
+    let history = data
+        .history
+        .iter()
+        .map(|&(guess, hints)| {
+            (
+                dictionary.words[guess].clone(),
+                dictionary.hints[hints].clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let knowledge = history
+        .iter()
+        .fold(KnowledgeN::none(), |knowledge, (guess, hints)| {
+            update_knowledge(guess, hints, knowledge)
+        });
+
+    let guess = match guess {
+        Some(guess) => guess,
+        None => {
+            let turn = Turn {
+                dictionary,
+                matrix: cache.matrix.as_deref(),
+                answers: &data.answers,
+                history: &history,
+                hard_mode: data.hard_mode,
+                entropies: history.is_empty().then(|| &cache.initial_entropies[..]),
+            };
+            data.strategy
+                .strategy(data.model.clone())
+                .choose_with_progress(&turn, progress)?
+        }
+    };
+
+    let guess_word = &dictionary.words[guess];
+    if data.hard_mode
+        && !KnowledgeFilter::new(&dictionary.translator, &knowledge)
+            .matches(&dictionary.words_bytes[guess])
+    {
+        return Err(anyhow!(
+            "Guess {guess_word} ignores some of the revealed hints, not allowed in hard mode"
+        ));
     }
+
+    let hints = match (hints, data.answer) {
+        (None, Answer::Adversarial) => match &cache.matrix {
+            Some(matrix) => adversary_hints_with_matrix::<N>(matrix, guess, &data.answers),
+            None => adversary_hints(dictionary, guess, &data.answers),
+        },
+        (Some(hints), Answer::Unknown) => hints,
+        (None, Answer::Known(correct)) => {
+            get_hints(guess_word, &dictionary.words[correct]).to_ind()
+        }
+        (Some(_), Answer::Known(_)) => {
+            return Err(anyhow!(
+                "Tried to pass custom hints to a simulation that already has a known solution"
+            ))
+        }
+        (Some(_), Answer::Adversarial) => {
+            return Err(anyhow!(
+                "Tried to pass custom hints to an adversarial simulation"
+            ))
+        }
+        (None, Answer::Unknown) => {
+            return Err(anyhow!(
+                "Didn't pass custom hints to a simulation that doesn't have a known solution"
+            ))
+        }
+    };
+    let knowledge = update_knowledge(guess_word, &dictionary.hints[hints], knowledge);
+
+    data.history.push((guess, hints));
+    data.answers = match &cache.matrix {
+        Some(matrix) => filter_answers(matrix, &data.answers, guess, hints),
+        None => get_answers(dictionary, &data.answers, &knowledge),
+    };
+
+    let uncertainty = uncertainty(dictionary, &data.answers);
+
+    let guesses = get_guesses(dictionary, &knowledge, data.hard_mode);
+    let entropies = guess_summaries_with_progress(
+        dictionary,
+        cache.matrix.as_deref(),
+        &guesses,
+        &data.answers[..],
+        progress,
+    )
+    .ok_or(Cancelled)?;
+    let scores = entropies_scored(
+        dictionary,
+        &data.answers[..],
+        entropies,
+        Some(uncertainty),
+        Some(&data.model),
+    )
+    .into_iter()
+    .take(10)
+    .collect::<Vec<_>>();
+
+    Ok(SimulationOutput::StepComplete {
+        guess,
+        hints: Some(hints),
+        uncertainty,
+        scores,
+        answers: data.answers.clone(),
+    })
 }
@@ -0,0 +1,252 @@
+use std::cell::RefCell;
+use std::future::Future;
+
+use anyhow::{anyhow, Result};
+use gloo_utils::window;
+use js_sys::{Array, Promise, Uint8Array};
+use serde_cbor::{from_slice, ser::to_vec_packed};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+use crate::word_set::WordSet;
+
+const DB_NAME: &str = "wordle-entropy";
+const DB_VERSION: u32 = 1;
+/// CBOR-encoded word sets, each keyed by its name, along with the list of the names
+const WORD_SETS_STORE: &str = "word_sets";
+/// Key of the names of the stored word sets, in the order they're shown
+const NAMES_KEY: &str = "word_sets_names";
+
+fn word_set_key(name: &str) -> String {
+    format!("word_set:{name}")
+}
+
+/// Word sets in the store, as far as this session knows
+#[derive(Default)]
+struct Stored {
+    word_sets: Vec<WordSet>,
+    /// Names of the stored word sets which couldn't be decoded, kept in the names list
+    /// so that they're not lost
+    unreadable: Vec<String>,
+}
+
+impl Stored {
+    fn names(&self, word_sets: &[WordSet]) -> Vec<String> {
+        let mut names = word_sets
+            .iter()
+            .map(|word_set| word_set.name.clone())
+            .collect::<Vec<_>>();
+        for name in &self.unreadable {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+}
+
+thread_local! {
+    /// `None` until the stored word sets are loaded. Nothing gets saved before that,
+    /// so that the word sets which failed to load aren't overwritten.
+    static STORED: RefCell<Option<Stored>> = RefCell::new(None);
+}
+
+/// Stored word sets, along with the errors of the ones which couldn't be decoded
+pub struct StoredWordSets {
+    pub word_sets: Vec<WordSet>,
+    pub errors: Vec<String>,
+}
+
+fn js_error(err: JsValue) -> anyhow::Error {
+    anyhow!("IndexedDB error: {err:?}")
+}
+
+/// Resolves with the result of the `request` once it succeeds. The handlers are set right away,
+/// so that all the requests of a transaction can be queued before waiting for any of them.
+fn wait_for(request: &IdbRequest) -> impl Future<Output = Result<JsValue>> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let success_request = request.clone();
+        let onsuccess = Closure::once_into_js(move || {
+            let result = success_request.result().unwrap_or(JsValue::UNDEFINED);
+            resolve.call1(&JsValue::NULL, &result).ok();
+        });
+        let onerror = Closure::once_into_js(move || {
+            reject
+                .call1(&JsValue::NULL, &JsValue::from_str("request failed"))
+                .ok();
+        });
+        request.set_onsuccess(Some(onsuccess.unchecked_ref()));
+        request.set_onerror(Some(onerror.unchecked_ref()));
+    });
+    let future = JsFuture::from(promise);
+
+    async move { future.await.map_err(js_error) }
+}
+
+async fn open_db() -> Result<IdbDatabase> {
+    let factory = window()
+        .indexed_db()
+        .map_err(js_error)?
+        .ok_or_else(|| anyhow!("IndexedDB is not available"))?;
+    let request = factory
+        .open_with_u32(DB_NAME, DB_VERSION)
+        .map_err(js_error)?;
+
+    let upgrade_request = request.clone();
+    let onupgradeneeded = Closure::once_into_js(move || {
+        if let Ok(db) = upgrade_request
+            .result()
+            .and_then(|db| db.dyn_into::<IdbDatabase>())
+        {
+            db.create_object_store(WORD_SETS_STORE).ok();
+        }
+    });
+    request.set_onupgradeneeded(Some(onupgradeneeded.unchecked_ref()));
+
+    wait_for(&request)
+        .await?
+        .dyn_into::<IdbDatabase>()
+        .map_err(js_error)
+}
+
+async fn open_store(mode: IdbTransactionMode) -> Result<IdbObjectStore> {
+    open_db()
+        .await?
+        .transaction_with_str_and_mode(WORD_SETS_STORE, mode)
+        .and_then(|transaction| transaction.object_store(WORD_SETS_STORE))
+        .map_err(js_error)
+}
+
+/// Decodes a stored word set, dropping the computed data
+/// which no longer matches its dictionary or calibration
+fn decode_word_set(value: &JsValue) -> Result<WordSet> {
+    let bytes = Uint8Array::new(value).to_vec();
+    let word_set: WordSet = from_slice(&bytes)?;
+    Ok(word_set.validated())
+}
+
+/// Word sets in the order they were saved. The ones which can't be decoded are skipped
+/// and kept in the store, only failing to read the store at all is an error.
+pub async fn load_word_sets() -> Result<StoredWordSets> {
+    let store = open_store(IdbTransactionMode::Readonly).await?;
+    // all the requests are queued before waiting, so that the transaction doesn't commit early
+    let keys = wait_for(&store.get_all_keys().map_err(js_error)?);
+    let values = wait_for(&store.get_all().map_err(js_error)?);
+    let (keys, values) = (Array::from(&keys.await?), Array::from(&values.await?));
+    let entries = keys.iter().zip(values.iter()).collect::<Vec<_>>();
+    let find_entry = |key: &str| {
+        entries
+            .iter()
+            .find(|(entry_key, _)| entry_key.as_string().as_deref() == Some(key))
+            .map(|(_, value)| value)
+    };
+
+    let mut stored = Stored::default();
+    let mut word_sets = vec![];
+    let mut errors = vec![];
+    if let Some(names) = find_entry(NAMES_KEY) {
+        let names: Vec<String> = from_slice(&Uint8Array::new(names).to_vec())?;
+        for name in names {
+            let word_set = find_entry(&word_set_key(&name))
+                .ok_or_else(|| anyhow!("missing from the store"))
+                .and_then(decode_word_set);
+            match word_set {
+                Ok(word_set) => word_sets.push(word_set),
+                Err(err) => {
+                    errors.push(format!("{name}: {err}"));
+                    stored.unreadable.push(name);
+                }
+            }
+        }
+        stored.word_sets = word_sets.clone();
+    }
+
+    STORED.with(|cell| *cell.borrow_mut() = Some(stored));
+
+    Ok(StoredWordSets { word_sets, errors })
+}
+
+/// Stores the `word_sets` which differ from the stored ones and removes the ones no longer
+/// in the list. Does nothing until the stored word sets are loaded with [`load_word_sets`].
+pub async fn save_word_sets(word_sets: &[WordSet]) -> Result<()> {
+    let changes = STORED.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let stored = cell.as_mut()?;
+
+        let changed = word_sets
+            .iter()
+            .filter(|word_set| {
+                !stored
+                    .word_sets
+                    .iter()
+                    .any(|stored_set| stored_set == *word_set)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let removed = stored
+            .word_sets
+            .iter()
+            .filter(|stored_set| {
+                !word_sets
+                    .iter()
+                    .any(|word_set| word_set.name == stored_set.name)
+            })
+            .map(|stored_set| stored_set.name.clone())
+            .collect::<Vec<_>>();
+        let old_names = stored.names(&stored.word_sets);
+        let names = stored.names(word_sets);
+        let names = (names != old_names).then(|| names);
+
+        stored
+            .unreadable
+            .retain(|name| !word_sets.iter().any(|word_set| &word_set.name == name));
+        stored.word_sets = word_sets.to_vec();
+
+        Some((changed, removed, names))
+    });
+    let (changed, removed, names) = match changes {
+        Some(changes) => changes,
+        None => return Ok(()),
+    };
+    if changed.is_empty() && removed.is_empty() && names.is_none() {
+        return Ok(());
+    }
+
+    let store = open_store(IdbTransactionMode::Readwrite).await?;
+    // all the requests are queued before waiting, so that the transaction doesn't commit early
+    let mut requests = vec![];
+    for word_set in &changed {
+        let bytes = to_vec_packed(word_set)?;
+        requests.push(
+            store
+                .put_with_key(
+                    &Uint8Array::from(&bytes[..]),
+                    &JsValue::from_str(&word_set_key(&word_set.name)),
+                )
+                .map_err(js_error)?,
+        );
+    }
+    for name in &removed {
+        requests.push(
+            store
+                .delete(&JsValue::from_str(&word_set_key(name)))
+                .map_err(js_error)?,
+        );
+    }
+    if let Some(names) = names {
+        let bytes = to_vec_packed(&names)?;
+        requests.push(
+            store
+                .put_with_key(&Uint8Array::from(&bytes[..]), &JsValue::from_str(NAMES_KEY))
+                .map_err(js_error)?,
+        );
+    }
+
+    let waiting = requests.iter().map(wait_for).collect::<Vec<_>>();
+    for request in waiting {
+        request.await?;
+    }
+
+    Ok(())
+}
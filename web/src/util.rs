@@ -1,4 +1,4 @@
-use crate::EntropiesData;
+use wordle_entropy_core::structs::EntropiesData;
 
 pub fn scores_without_full_data(
     scores: Vec<(usize, EntropiesData, f64)>,
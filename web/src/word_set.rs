@@ -1,24 +1,39 @@
 use crate::components::WordSetSelection;
-use crate::{Dictionary, EntropiesData};
+use crate::storage::save_word_sets;
+use anyhow::{anyhow, Result};
 use bounce::prelude::*;
+use fxhash::FxHasher;
 use gloo_storage::{LocalStorage, Storage};
 use serde::{Deserialize, Serialize};
+use serde_cbor::{from_slice, ser::to_vec_packed};
+use std::hash::{Hash, Hasher};
 use std::iter;
 use std::rc::Rc;
-use wordle_entropy_core::calibration::Calibration;
+use std::str::Lines;
+use std::sync::Arc;
+use wasm_bindgen_futures::spawn_local;
+use wordle_entropy_core::calibration::{Calibration, Model};
+use wordle_entropy_core::data::{parse_words_any_with, LoadOptions, LoadWarning};
+use wordle_entropy_core::dispatch_dictionary;
+use wordle_entropy_core::optimal::StrategyTree;
+use wordle_entropy_core::priors::Prior;
+use wordle_entropy_core::structs::{AnyDictionary, Dictionary, EntropiesData, HintMatrix};
 use yew::Reducible;
 
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SetCalibration {
     Default,
     Custom(Calibration),
+    /// Model of any of the families, fitted in the calibration tab or loaded from a file
+    Model(Model),
 }
 
 impl SetCalibration {
-    pub fn get_calibration(&self) -> Calibration {
+    pub fn get_model(&self) -> Model {
         match self {
-            SetCalibration::Default => Calibration::default(),
-            SetCalibration::Custom(calibration) => *calibration,
+            SetCalibration::Default => Model::default(),
+            SetCalibration::Custom(calibration) => Model::Log(*calibration),
+            SetCalibration::Model(model) => model.clone(),
         }
     }
 }
@@ -32,19 +47,138 @@ impl Default for SetCalibration {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WordSet {
     pub name: String,
-    pub dictionary: Rc<Dictionary>,
+    pub dictionary: Rc<AnyDictionary>,
     pub entropies: Option<Rc<Vec<(usize, EntropiesData, f64)>>>,
+    #[serde(default)]
+    pub strategy: Option<Rc<StrategyTree>>,
     pub calibration: SetCalibration,
+    #[serde(default)]
+    pub prior: Prior,
+    /// Probabilities as loaded, before the prior was applied; the dictionary ones if missing
+    #[serde(default)]
+    pub raw_probabilities: Option<Rc<Vec<f64>>>,
+    /// Hint matrix built by the worker, only depends on the words and answers of the dictionary
+    #[serde(default)]
+    pub matrix: Option<Arc<HintMatrix>>,
+    /// [`WordSet::fingerprint`] of the word set the entropies and strategy were computed for
+    #[serde(default)]
+    pub computed_for: Option<u64>,
 }
 
 impl WordSet {
-    pub fn from_dictionary(name: String, dictionary: Dictionary) -> Self {
+    pub fn from_dictionary(name: String, dictionary: AnyDictionary) -> Self {
         Self {
             name,
             dictionary: Rc::new(dictionary),
             entropies: None,
+            strategy: None,
             calibration: SetCalibration::default(),
+            prior: Prior::default(),
+            raw_probabilities: None,
+            matrix: None,
+            computed_for: None,
+        }
+    }
+
+    /// Hash of the dictionary and calibration everything computed for the word set depends on
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        dispatch_dictionary!(&*self.dictionary, dictionary => dictionary.words.hash(&mut hasher));
+        self.dictionary.answers().hash(&mut hasher);
+        for probability in self.dictionary.probabilities() {
+            probability.to_bits().hash(&mut hasher);
+        }
+        to_vec_packed(&self.calibration.get_model())
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Drops the computed data if it was computed for a different dictionary or calibration
+    pub fn validated(self) -> Self {
+        if self.computed_for == Some(self.fingerprint()) {
+            self
+        } else {
+            Self {
+                entropies: None,
+                strategy: None,
+                computed_for: None,
+                ..self
+            }
+        }
+    }
+
+    /// Same word set with the `prior` applied to the loaded probabilities,
+    /// drops everything computed from the old ones
+    pub fn with_prior(&self, prior: Prior) -> Self {
+        let raw_probabilities = self
+            .raw_probabilities
+            .clone()
+            .unwrap_or_else(|| Rc::new(self.dictionary.probabilities().to_vec()));
+        let mut dictionary = (*self.dictionary).clone();
+        dispatch_dictionary!(&mut dictionary, dictionary => {
+            dictionary.probabilities = prior.apply(&raw_probabilities)
+        });
+
+        Self {
+            name: self.name.clone(),
+            dictionary: Rc::new(dictionary),
+            entropies: None,
+            strategy: None,
+            calibration: self.calibration.clone(),
+            prior,
+            raw_probabilities: Some(raw_probabilities),
+            matrix: self.matrix.clone(),
+            computed_for: None,
+        }
+    }
+
+    /// Encodes the word set as a versioned file which can be imported back,
+    /// the computed data is optional as it can make the file much bigger
+    pub fn export(&self, include_computed: bool) -> Result<Vec<u8>> {
+        let word_set = if include_computed {
+            self.clone()
+        } else {
+            Self {
+                entropies: None,
+                strategy: None,
+                matrix: None,
+                computed_for: None,
+                ..self.clone()
+            }
+        };
+
+        Ok(to_vec_packed(&ExportedWordSet {
+            version: EXPORT_VERSION,
+            word_set,
+        })?)
+    }
+
+    /// Decodes a word set exported by [`WordSet::export`], either as CBOR or as JSON
+    pub fn import(content: &[u8]) -> Result<Self> {
+        let is_json = content
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace())
+            .map_or(false, |&byte| byte == b'{');
+        let header: ExportHeader = if is_json {
+            serde_json::from_slice(content)?
+        } else {
+            from_slice(content)?
+        };
+        if header.version > EXPORT_VERSION {
+            return Err(anyhow!(
+                "Word set file version {} is newer than the supported version {EXPORT_VERSION}",
+                header.version
+            ));
         }
+
+        let exported: ExportedWordSet = if is_json {
+            serde_json::from_slice(content)?
+        } else {
+            from_slice(content)?
+        };
+
+        Ok(exported.word_set.validated())
     }
 
     pub fn without_entropies(&self) -> Self {
@@ -52,7 +186,12 @@ impl WordSet {
             name: self.name.clone(),
             dictionary: self.dictionary.clone(),
             entropies: None,
-            calibration: self.calibration,
+            strategy: self.strategy.clone(),
+            calibration: self.calibration.clone(),
+            prior: self.prior,
+            raw_probabilities: self.raw_probabilities.clone(),
+            matrix: self.matrix.clone(),
+            computed_for: self.computed_for,
         }
     }
 
@@ -64,68 +203,105 @@ impl WordSet {
                 .entropies
                 .as_ref()
                 .map(|e| Rc::new(e.iter().cloned().take(number_to_take).collect())),
-            calibration: self.calibration,
+            strategy: self.strategy.clone(),
+            calibration: self.calibration.clone(),
+            prior: self.prior,
+            raw_probabilities: self.raw_probabilities.clone(),
+            matrix: self.matrix.clone(),
+            computed_for: self.computed_for,
         }
     }
 }
 
+/// Version of the exported word set files, bumped whenever the format changes
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct ExportedWordSet {
+    version: u32,
+    word_set: WordSet,
+}
+
+/// Only the version of [`ExportedWordSet`], checked before decoding the rest
+#[derive(Deserialize)]
+struct ExportHeader {
+    version: u32,
+}
+
 pub enum WordSetVecAction {
     Set(WordSetVec),
+    Add(WordSet),
     Remove(String),
-    LoadWords(String, Dictionary),
+    LoadWords(String, AnyDictionary, Prior),
     SetEntropy(String, Rc<Vec<(usize, EntropiesData, f64)>>),
+    SetStrategy(String, Rc<StrategyTree>),
+    SetMatrix(String, Arc<HintMatrix>),
     SetCalibration(String, SetCalibration),
+    SetPrior(String, Prior),
 }
 
-#[derive(Clone, Debug, PartialEq, Slice, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Slice, Serialize, Deserialize)]
 #[observed]
 pub struct WordSetVec(pub Vec<WordSet>);
 
 static STORAGE_VEC_NAMES: &str = "word_sets_names";
 
+/// Word set as saved in the local storage by the older versions, which only had 5-letter words
+#[derive(Deserialize)]
+struct LocalStorageWordSet {
+    name: String,
+    dictionary: Dictionary<5>,
+    calibration: SetCalibration,
+}
+
 fn get_word_set_storage_key(name: &str) -> String {
     format!("word_set:{name}")
 }
 
-impl Default for WordSetVec {
-    fn default() -> Self {
+impl WordSetVec {
+    pub fn extend_with(&self, word_sets: impl IntoIterator<Item = WordSet>) -> Self {
+        let mut new_vec = self.clone();
+        new_vec.0.extend(word_sets);
+        new_vec
+    }
+
+    /// Word sets saved by the older versions, without any of the computed data
+    pub fn from_local_storage() -> Self {
         let names: Option<Vec<String>> = LocalStorage::get(STORAGE_VEC_NAMES).ok();
         let vec = match names {
             Some(names) => names
                 .iter()
                 .filter_map(|name| LocalStorage::get(get_word_set_storage_key(name)).ok())
+                .map(|word_set: LocalStorageWordSet| WordSet {
+                    calibration: word_set.calibration,
+                    ..WordSet::from_dictionary(
+                        word_set.name,
+                        AnyDictionary::Five(word_set.dictionary),
+                    )
+                })
                 .collect(),
             None => vec![],
         };
 
         WordSetVec(vec)
     }
-}
 
-impl WordSetVec {
-    pub fn extend_with(&self, word_sets: impl IntoIterator<Item = WordSet>) -> Self {
-        let mut new_vec = self.clone();
-        new_vec.0.extend(word_sets);
-        new_vec
+    /// Removes the word sets saved by the older versions, once they are kept in IndexedDB
+    pub fn clear_local_storage(&self) {
+        for word_set in &self.0 {
+            LocalStorage::delete(get_word_set_storage_key(&word_set.name));
+        }
+        LocalStorage::delete(STORAGE_VEC_NAMES);
     }
 }
 
 impl Observed for WordSetVec {
     fn changed(self: Rc<Self>) {
-        let names = self.0.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
-        LocalStorage::set(STORAGE_VEC_NAMES, names).ok();
-        for word_set_without_entropies in self.0.iter().map(|word_set| WordSet {
-            name: word_set.name.clone(),
-            dictionary: word_set.dictionary.clone(),
-            entropies: None,
-            calibration: word_set.calibration,
-        }) {
-            LocalStorage::set(
-                get_word_set_storage_key(&word_set_without_entropies.name),
-                word_set_without_entropies,
-            )
-            .expect("Failed to set word set collection");
-        }
+        spawn_local(async move {
+            if let Err(err) = save_word_sets(&self.0).await {
+                log::error!("Failed to save the word sets: {err}");
+            }
+        });
     }
 }
 
@@ -142,8 +318,10 @@ impl Reducible for WordSetVec {
                     .cloned()
                     .collect(),
             )),
-            WordSetVecAction::LoadWords(name, dictionary) => {
-                Rc::new(self.extend_with(iter::once(WordSet::from_dictionary(name, dictionary))))
+            WordSetVecAction::Add(word_set) => Rc::new(self.extend_with(iter::once(word_set))),
+            WordSetVecAction::LoadWords(name, dictionary, prior) => {
+                let word_set = WordSet::from_dictionary(name, dictionary).with_prior(prior);
+                Rc::new(self.extend_with(iter::once(word_set)))
             }
             WordSetVecAction::SetEntropy(name, entropies_data) => {
                 let mut new_vec = self.0.clone();
@@ -151,6 +329,26 @@ impl Reducible for WordSetVec {
                 new_vec.iter_mut().for_each(|word_set| {
                     if word_set.name == name {
                         word_set.entropies = entropies_data.take();
+                        word_set.computed_for = Some(word_set.fingerprint());
+                    }
+                });
+                Rc::new(WordSetVec(new_vec))
+            }
+            WordSetVecAction::SetStrategy(name, strategy) => {
+                let mut new_vec = self.0.clone();
+                new_vec.iter_mut().for_each(|word_set| {
+                    if word_set.name == name {
+                        word_set.strategy = Some(strategy.clone());
+                        word_set.computed_for = Some(word_set.fingerprint());
+                    }
+                });
+                Rc::new(WordSetVec(new_vec))
+            }
+            WordSetVecAction::SetMatrix(name, matrix) => {
+                let mut new_vec = self.0.clone();
+                new_vec.iter_mut().for_each(|word_set| {
+                    if word_set.name == name {
+                        word_set.matrix = Some(matrix.clone());
                     }
                 });
                 Rc::new(WordSetVec(new_vec))
@@ -159,7 +357,17 @@ impl Reducible for WordSetVec {
                 let mut new_vec = self.0.clone();
                 new_vec.iter_mut().for_each(|word_set| {
                     if word_set.name == name {
-                        word_set.calibration = calibration;
+                        word_set.calibration = calibration.clone();
+                        *word_set = word_set.clone().validated();
+                    }
+                });
+                Rc::new(WordSetVec(new_vec))
+            }
+            WordSetVecAction::SetPrior(name, prior) => {
+                let mut new_vec = self.0.clone();
+                new_vec.iter_mut().for_each(|word_set| {
+                    if word_set.name == name {
+                        *word_set = word_set.with_prior(prior);
                     }
                 });
                 Rc::new(WordSetVec(new_vec))
@@ -178,7 +386,7 @@ pub fn get_current_word_set() -> WordSet {
         .cloned()
         .unwrap_or(WordSet::from_dictionary(
             "invalid_word_set".to_string(),
-            Dictionary::new(vec![], vec![]),
+            AnyDictionary::Five(Dictionary::new(vec![], vec![])),
         ));
 
     word_set
@@ -194,3 +402,37 @@ pub struct WordSetSpec {
 pub struct DefaultWordSets {
     pub word_sets: Vec<WordSetSpec>,
 }
+
+/// Default word sets compiled into the app, used when they can't be downloaded
+const BUNDLED_WORD_SETS: [(&str, &str); 2] = [
+    (
+        "Polish words",
+        include_str!("../static/data/polish_words_with_probs.csv"),
+    ),
+    (
+        "English words",
+        include_str!("../static/data/english_words_with_probs.csv"),
+    ),
+];
+
+pub fn bundled_word_sets() -> Result<Vec<WordSet>> {
+    BUNDLED_WORD_SETS
+        .iter()
+        .map(|(name, content)| {
+            let (dictionary, _) = parse_dictionary(content, &LoadOptions::default())?;
+            Ok(WordSet::from_dictionary(name.to_string(), dictionary))
+        })
+        .collect()
+}
+
+/// Parses a word list of any of the supported lengths, taken from its first word
+pub fn parse_dictionary(
+    content: &str,
+    options: &LoadOptions,
+) -> Result<(AnyDictionary, Vec<LoadWarning>)> {
+    Ok(parse_words_any_with(
+        content.lines(),
+        None::<Lines>,
+        options,
+    )?)
+}
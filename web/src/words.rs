@@ -0,0 +1,124 @@
+//! Helpers for the pages, which only know the word length of the selected word set
+//! at runtime. They dispatch on its [`AnyDictionary`] and pass the guesses and hints
+//! around by their indices.
+
+use anyhow::{anyhow, Result};
+use wordle_entropy_core::algo::{get_valid_hints, update_knowledge};
+use wordle_entropy_core::dispatch_dictionary;
+use wordle_entropy_core::multi_board::{MultiBoard, MultiBoardError};
+use wordle_entropy_core::structs::hints::{Hint, ValidHints};
+use wordle_entropy_core::structs::knowledge::KnowledgeN;
+use wordle_entropy_core::structs::{AnyDictionary, Dictionary, HintsN, WordError};
+
+/// Index of the `hints`, as in [`HintsN::to_ind`]
+pub fn hints_ind(hints: &[Hint]) -> usize {
+    hints.iter().fold(0, |ind, &hint| ind * 3 + hint as usize)
+}
+
+/// Index of the hints of a solved puzzle
+pub fn correct_hints(word_length: usize) -> usize {
+    hints_ind(&vec![Hint::Correct; word_length])
+}
+
+pub fn parse_word(word: &str, dictionary: &AnyDictionary) -> Result<usize> {
+    if word.chars().count() != dictionary.word_length() {
+        return Err(WordError::IncorrectLength {
+            word: word.to_string(),
+            expected_length: dictionary.word_length(),
+        }
+        .into());
+    }
+
+    dictionary
+        .position(word)
+        .ok_or_else(|| anyhow!("Word {word} not found in the current word set!"))
+}
+
+fn guess_valid_hints<const N: usize>(
+    dictionary: &Dictionary<N>,
+    knowledge: &KnowledgeN<N>,
+    guess: usize,
+    hints: &[Hint],
+) -> ValidHints {
+    let hints = HintsN(hints.try_into().expect("Hints of a different word length"));
+    get_valid_hints(&dictionary.words[guess], &hints, knowledge)
+}
+
+/// Hints each letter of the `guess` can get after the `history` of the guesses
+/// and their hints, given the `hints` of the other letters
+pub fn valid_hints_after(
+    dictionary: &AnyDictionary,
+    history: &[(usize, usize)],
+    guess: usize,
+    hints: &[Hint],
+) -> ValidHints {
+    dispatch_dictionary!(dictionary, dictionary => {
+        let knowledge = history
+            .iter()
+            .fold(KnowledgeN::none(), |knowledge, &(guess, hints)| {
+                update_knowledge(&dictionary.words[guess], &dictionary.hints[hints], knowledge)
+            });
+        guess_valid_hints(dictionary, &knowledge, guess, hints)
+    })
+}
+
+/// Multi-board game after the `guesses`, each with the hints of every board
+pub fn multi_board<const N: usize>(
+    dictionary: &Dictionary<N>,
+    boards: usize,
+    guesses: &[(usize, Vec<usize>)],
+) -> Result<MultiBoard<N>, MultiBoardError> {
+    let mut game = MultiBoard::new(dictionary, boards);
+    for (guess, hints) in guesses {
+        let hints = hints
+            .iter()
+            .map(|&hints| dictionary.hints[hints].clone())
+            .collect::<Vec<_>>();
+        game.add_guess(dictionary, *guess, &hints)?;
+    }
+
+    Ok(game)
+}
+
+/// What the pages show of a board of a multi-board game
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoardState {
+    /// Number of the answers consistent with the hints of the board
+    pub answers: usize,
+    pub solved: bool,
+}
+
+pub fn multi_board_states(
+    dictionary: &AnyDictionary,
+    boards: usize,
+    guesses: &[(usize, Vec<usize>)],
+) -> Result<Vec<BoardState>, MultiBoardError> {
+    dispatch_dictionary!(dictionary, dictionary => {
+        let game = multi_board(dictionary, boards, guesses)?;
+        Ok(game
+            .boards
+            .iter()
+            .map(|board| BoardState {
+                answers: board.answers.len(),
+                solved: board.solved,
+            })
+            .collect())
+    })
+}
+
+/// [`valid_hints_after`] of the `board` of a multi-board game
+pub fn multi_board_valid_hints(
+    dictionary: &AnyDictionary,
+    boards: usize,
+    guesses: &[(usize, Vec<usize>)],
+    board: usize,
+    guess: usize,
+    hints: &[Hint],
+) -> ValidHints {
+    dispatch_dictionary!(dictionary, dictionary => {
+        let knowledge = multi_board(dictionary, boards, guesses)
+            .map(|game| game.boards[board].knowledge.clone())
+            .unwrap_or_default();
+        guess_valid_hints(dictionary, &knowledge, guess, hints)
+    })
+}
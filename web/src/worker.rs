@@ -1,12 +1,58 @@
 use crate::simulation::{Simulation, SimulationInput, SimulationOutput};
 use crate::word_set::WordSet;
-use crate::EntropiesData;
+use crate::words::multi_board;
 use anyhow::{anyhow, Result};
+use gloo_timers::future::TimeoutFuture;
 use gloo_worker::{HandlerId, Public, Worker, WorkerLink};
 use serde::{Deserialize, Serialize};
 use serde_cbor::from_slice;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
-use wordle_entropy_core::entropy::{calculate_entropies, entropies_scored};
+use std::sync::{Arc, Mutex};
+use wasm_bindgen_futures::spawn_local;
+use wordle_entropy_core::dispatch_dictionary;
+use wordle_entropy_core::entropy::{
+    calculate_entropies_with_matrix_and_progress, calculate_entropies_with_progress,
+    entropies_scored,
+};
+use wordle_entropy_core::multi_board::{score_guesses, MultiBoardOptions, MultiBoardScore};
+use wordle_entropy_core::optimal::{optimal_tree, OptimalOptions, StrategyTree};
+use wordle_entropy_core::progress::Progress;
+use wordle_entropy_core::structs::{AnyDictionary, EntropiesData, HintMatrix};
+
+/// Number of the guesses with the highest entropy tried at every node of the strategy tree,
+/// so the tree is a heuristic rather than the optimal one
+const STRATEGY_CANDIDATES: usize = 20;
+/// Largest number of answers the strategy tree is searched for
+const STRATEGY_MAX_ANSWERS: usize = 500;
+const MULTI_BOARD_SCORES: usize = 10;
+/// How often the finished calculations are looked for
+const POLL_INTERVAL_MS: u32 = 10;
+/// How often the progress of the calculations is reported
+const PROGRESS_INTERVAL_MS: u32 = 250;
+/// Largest size in bytes of the hint matrix built
+const MATRIX_MAX_BYTES: usize = 80_000_000;
+
+/// Whether the hint matrix of the `dictionary` is small enough to be built
+fn fits_matrix(dictionary: &AnyDictionary) -> bool {
+    let hint_size = HintMatrix::hint_size(dictionary.word_length());
+    dictionary.words_len() * dictionary.answers().len() * hint_size <= MATRIX_MAX_BYTES
+}
+
+/// Hint matrix of the word set dictionary, built on the first calculation needing it
+/// and shared by the following ones
+#[derive(Default)]
+struct MatrixCache(Option<(Rc<AnyDictionary>, Arc<HintMatrix>)>);
+
+impl MatrixCache {
+    fn get(&self, dictionary: &Rc<AnyDictionary>) -> Option<Arc<HintMatrix>> {
+        match &self.0 {
+            Some((cached, matrix)) if Rc::ptr_eq(cached, dictionary) => Some(matrix.clone()),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 pub enum WordleWorkerInput {
@@ -14,7 +60,15 @@ pub enum WordleWorkerInput {
     SetWordSet(WordSet),
     SetWordSetEncoded(Vec<u8>),
     Entropy(String),
+    Strategy(String),
     Simulation(SimulationInput),
+    /// Guesses played on all of the `boards`, each with the hints of every board
+    MultiBoard {
+        boards: usize,
+        guesses: Vec<(usize, Vec<usize>)>,
+    },
+    /// Stops the running calculation of the bridge
+    Cancel,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -24,48 +78,327 @@ pub enum WordleWorkerOutput {
         entropies: bool,
     },
     SetWordSet(String),
-    Entropy(String, Vec<(usize, EntropiesData, f64)>),
+    /// Entropies of the word set, along with the hint matrix if it was built for them
+    Entropy(
+        String,
+        Vec<(usize, EntropiesData, f64)>,
+        Option<Arc<HintMatrix>>,
+    ),
+    /// Number of the guesses done so far, out of the total
+    Progress {
+        done: usize,
+        total: usize,
+    },
+    Cancelled,
+    /// Strategy tree of the word set, along with the hint matrix if it was built for it
+    Strategy(String, StrategyTree, Option<Arc<HintMatrix>>),
     Simulation(SimulationOutput),
+    MultiBoard(Vec<MultiBoardScore>),
     Err(String),
 }
 
 pub struct WordleWorker {
     link: WorkerLink<Self>,
     word_set: Option<Rc<WordSet>>,
-    simulation: Simulation,
+    simulation: Rc<RefCell<Simulation>>,
+    /// Progress of the running calculation of each of the bridges
+    jobs: Rc<RefCell<HashMap<HandlerId, Arc<Progress>>>>,
+    matrix: Rc<RefCell<MatrixCache>>,
 }
 
 impl WordleWorker {
-    fn handle_entropy(&mut self, name: &String) -> Result<WordleWorkerOutput> {
+    /// Runs the `job` on the thread pool, so that the worker keeps handling the messages
+    /// in the meantime and the job can be cancelled. Responds with the progress until
+    /// the job returns, then with its `output`, `None` meaning the job was cancelled.
+    /// A job still running for the same bridge is cancelled and its results are dropped.
+    fn spawn_job<T: Send + 'static>(
+        &mut self,
+        id: HandlerId,
+        job: impl FnOnce(&Progress) -> Option<T> + Send + 'static,
+        output: impl FnOnce(T) -> WordleWorkerOutput + 'static,
+    ) {
+        let progress = Arc::new(Progress::new());
+        let result = Arc::new(Mutex::new(None::<Option<T>>));
+        if let Some(previous) = self.jobs.borrow_mut().insert(id, progress.clone()) {
+            previous.cancel();
+        }
+
+        {
+            let progress = progress.clone();
+            let result = result.clone();
+            rayon::spawn(move || {
+                let finished = job(&progress);
+                *result.lock().unwrap() = Some(finished);
+            });
+        }
+
+        let link = self.link.clone();
+        let jobs = self.jobs.clone();
+        spawn_local(async move {
+            let mut waited = 0;
+            loop {
+                TimeoutFuture::new(POLL_INTERVAL_MS).await;
+                let current = jobs
+                    .borrow()
+                    .get(&id)
+                    .is_some_and(|running| Arc::ptr_eq(running, &progress));
+                // replaced by a newer job or the bridge was disconnected
+                if !current {
+                    break;
+                }
+
+                let finished = result.lock().unwrap().take();
+                match finished {
+                    Some(finished) => {
+                        jobs.borrow_mut().remove(&id);
+                        let response = match finished {
+                            Some(value) => output(value),
+                            None => WordleWorkerOutput::Cancelled,
+                        };
+                        link.respond(id, response);
+                        break;
+                    }
+                    None => {
+                        waited += POLL_INTERVAL_MS;
+                        if waited >= PROGRESS_INTERVAL_MS {
+                            waited = 0;
+                            link.respond(
+                                id,
+                                WordleWorkerOutput::Progress {
+                                    done: progress.done(),
+                                    total: progress.total(),
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn handle_entropy(&mut self, name: String, id: HandlerId) -> Result<()> {
         let word_set = self.word_set.as_ref().ok_or(anyhow!(
             "Worker was not initialized correctly, missing word sets"
         ))?;
-        let dictionary = word_set.dictionary.clone();
-        let answers = (0..dictionary.words.len()).collect::<Vec<_>>();
-        let entropies = calculate_entropies(&dictionary, &answers);
-
-        let scores = entropies_scored(
-            &dictionary,
-            &answers,
-            entropies,
-            None,
-            Some(word_set.calibration.get_calibration()),
+        let word_set_dictionary = word_set.dictionary.clone();
+        let dictionary = (*word_set.dictionary).clone();
+        let calibration = word_set.calibration.get_model();
+        let cached_matrix = self.matrix.borrow().get(&word_set_dictionary);
+        let built_matrix = cached_matrix.is_none();
+        let matrix_cache = self.matrix.clone();
+
+        self.spawn_job(
+            id,
+            move |progress| {
+                let matrix_fits = fits_matrix(&dictionary);
+                dispatch_dictionary!(&dictionary, dictionary => {
+                    let guesses = (0..dictionary.words.len()).collect::<Vec<_>>();
+                    let answers = &dictionary.answers;
+                    // kept by the worker, so that the simulations don't have to build it again.
+                    // Its rows are reported to the progress before the entropies are.
+                    let matrix = match cached_matrix {
+                        Some(matrix) => Some(matrix),
+                        None if matrix_fits => Some(Arc::new(
+                            HintMatrix::with_progress(dictionary, progress).ok()?,
+                        )),
+                        None => None,
+                    };
+                    let entropies = match &matrix {
+                        Some(matrix) => calculate_entropies_with_matrix_and_progress(
+                            dictionary,
+                            matrix,
+                            &guesses,
+                            answers,
+                            progress,
+                        ),
+                        None => calculate_entropies_with_progress(
+                            dictionary, &guesses, answers, progress,
+                        ),
+                    };
+                    entropies.map(|entropies| {
+                        let scores = entropies_scored(
+                            dictionary,
+                            answers,
+                            entropies,
+                            None,
+                            Some(&calibration),
+                        );
+                        (scores, matrix)
+                    })
+                })
+            },
+            move |(scores, matrix)| {
+                if let Some(matrix) = &matrix {
+                    matrix_cache.borrow_mut().0 = Some((word_set_dictionary, matrix.clone()));
+                }
+                // sent back to be saved with the word set, unless it was already there
+                WordleWorkerOutput::Entropy(name, scores, matrix.filter(|_| built_matrix))
+            },
         );
 
-        Ok(WordleWorkerOutput::Entropy(name.clone(), scores))
+        Ok(())
+    }
+
+    /// The job is left in place, so that it still responds once it stops
+    fn handle_cancel(&mut self, id: HandlerId) {
+        if let Some(progress) = self.jobs.borrow().get(&id) {
+            progress.cancel();
+        }
+    }
+
+    fn handle_strategy(&mut self, name: String, id: HandlerId) -> Result<()> {
+        let word_set = self.word_set.as_ref().ok_or(anyhow!(
+            "Worker was not initialized correctly, missing word sets"
+        ))?;
+        let word_set_dictionary = word_set.dictionary.clone();
+        let dictionary = (*word_set.dictionary).clone();
+        // searching through every guess is only feasible for small word sets
+        if dictionary.answers().len() > STRATEGY_MAX_ANSWERS {
+            return Err(anyhow!(
+                "The strategy tree can only be found for at most {} answers, {} has {}",
+                STRATEGY_MAX_ANSWERS,
+                name,
+                dictionary.answers().len()
+            ));
+        }
+        if !fits_matrix(&dictionary) {
+            return Err(anyhow!(
+                "The strategy tree can't be found for {name}, it has too many words"
+            ));
+        }
+        let options = OptimalOptions {
+            candidates: Some(STRATEGY_CANDIDATES),
+            ..Default::default()
+        };
+        let cached_matrix = self.matrix.borrow().get(&word_set_dictionary);
+        let built_matrix = cached_matrix.is_none();
+        let matrix_cache = self.matrix.clone();
+
+        self.spawn_job(
+            id,
+            move |progress| {
+                dispatch_dictionary!(&dictionary, dictionary => {
+                    let matrix = match cached_matrix {
+                        Some(matrix) => matrix,
+                        None => Arc::new(HintMatrix::with_progress(dictionary, progress).ok()?),
+                    };
+                    optimal_tree(dictionary, &matrix, &options, progress)
+                        .ok()
+                        .map(|strategy| (strategy, matrix))
+                })
+            },
+            move |(strategy, matrix)| {
+                matrix_cache.borrow_mut().0 = Some((word_set_dictionary, matrix.clone()));
+                let matrix = built_matrix.then_some(matrix);
+                match strategy {
+                    Some(strategy) => WordleWorkerOutput::Strategy(name, strategy, matrix),
+                    None => WordleWorkerOutput::Err(format!(
+                        "No strategy finds every answer within {} guesses",
+                        options.max_depth
+                    )),
+                }
+            },
+        );
+
+        Ok(())
+    }
+
+    fn handle_multi_board(
+        &mut self,
+        boards: usize,
+        guesses: Vec<(usize, Vec<usize>)>,
+        id: HandlerId,
+    ) -> Result<()> {
+        let word_set = self.word_set.as_ref().ok_or(anyhow!(
+            "Worker was not initialized correctly, missing word sets"
+        ))?;
+        let dictionary = (*word_set.dictionary).clone();
+
+        self.spawn_job(
+            id,
+            move |progress| {
+                dispatch_dictionary!(&dictionary, dictionary => {
+                    match multi_board(dictionary, boards, &guesses) {
+                        Ok(game) => {
+                            score_guesses(dictionary, &game, &MultiBoardOptions::default(), progress)
+                                .ok()
+                                .map(Ok)
+                        }
+                        Err(err) => Some(Err(err.to_string())),
+                    }
+                })
+            },
+            |scores| match scores {
+                Ok(mut scores) => {
+                    scores.truncate(MULTI_BOARD_SCORES);
+                    WordleWorkerOutput::MultiBoard(scores)
+                }
+                Err(err) => WordleWorkerOutput::Err(err),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Plays the step of the simulation on the thread pool, responds right away if
+    /// the simulation was stopped instead
+    fn handle_simulation(
+        &mut self,
+        input: SimulationInput,
+        id: HandlerId,
+    ) -> Result<Option<WordleWorkerOutput>> {
+        let word_set = self.word_set.as_ref().ok_or(anyhow!(
+            "Tried to start simulation, but word set is not set".to_string()
+        ))?;
+        let word_set_dictionary = word_set.dictionary.clone();
+        let matrix = self.matrix.borrow().get(&word_set_dictionary);
+        let build_matrix = fits_matrix(&word_set_dictionary);
+        let job =
+            self.simulation
+                .borrow_mut()
+                .handle_message(word_set, matrix, build_matrix, input)?;
+        let job = match job {
+            Some(job) => job,
+            None => {
+                return Ok(Some(WordleWorkerOutput::Simulation(
+                    SimulationOutput::Stopped,
+                )))
+            }
+        };
+        let simulation = self.simulation.clone();
+        let matrix_cache = self.matrix.clone();
+
+        self.spawn_job(
+            id,
+            move |progress| job.run(progress),
+            move |step| {
+                // kept for the following calculations, the simulation may have built it
+                if let Some(matrix) = step.matrix() {
+                    matrix_cache.borrow_mut().0 = Some((word_set_dictionary.clone(), matrix));
+                }
+                match simulation.borrow_mut().finish(word_set_dictionary, step) {
+                    Ok(output) => WordleWorkerOutput::Simulation(output),
+                    Err(err) => WordleWorkerOutput::Err(err.to_string()),
+                }
+            },
+        );
+
+        Ok(None)
     }
 
     fn handle_set(&mut self, word_set: WordSet) -> Result<WordleWorkerOutput> {
         let name = word_set.name.clone();
+        // the saved matrix is used instead of building it again
+        if let Some(matrix) = &word_set.matrix {
+            self.matrix.borrow_mut().0 = Some((word_set.dictionary.clone(), matrix.clone()));
+        }
         self.word_set = Some(Rc::new(word_set));
         Ok(WordleWorkerOutput::SetWordSet(name))
     }
 
     fn handle_set_encoded(&mut self, word_set: Vec<u8>) -> Result<WordleWorkerOutput> {
         let word_set: WordSet = from_slice(&word_set[..]).unwrap();
-        let name = word_set.name.clone();
-        self.word_set = Some(Rc::new(word_set));
-        Ok(WordleWorkerOutput::SetWordSet(name))
+        self.handle_set(word_set)
     }
 
     fn handle_check_entropies(&mut self) -> Result<WordleWorkerOutput> {
@@ -89,7 +422,9 @@ impl Worker for WordleWorker {
         Self {
             link,
             word_set: None,
-            simulation: Simulation::default(),
+            simulation: Rc::default(),
+            jobs: Rc::default(),
+            matrix: Rc::default(),
         }
     }
 
@@ -100,18 +435,31 @@ impl Worker for WordleWorker {
             WordleWorkerInput::CheckEntropies => self.handle_check_entropies(),
             WordleWorkerInput::SetWordSet(word_set) => self.handle_set(word_set),
             WordleWorkerInput::SetWordSetEncoded(word_set) => self.handle_set_encoded(word_set),
-            WordleWorkerInput::Entropy(name) => self.handle_entropy(&name),
-            WordleWorkerInput::Simulation(input) => {
-                if let Some(word_set) = self.word_set.as_ref() {
-                    self.simulation
-                        .handle_message(&word_set, input)
-                        .map(|output| WordleWorkerOutput::Simulation(output))
-                } else {
-                    Err(anyhow!(
-                        "Tried to start simulation, but word set is not set".to_string()
-                    ))
+            WordleWorkerInput::Entropy(name) => match self.handle_entropy(name, id) {
+                // the results are sent once the calculation is done
+                Ok(()) => return,
+                Err(err) => Err(err),
+            },
+            WordleWorkerInput::Cancel => {
+                self.handle_cancel(id);
+                return;
+            }
+            WordleWorkerInput::Strategy(name) => match self.handle_strategy(name, id) {
+                Ok(()) => return,
+                Err(err) => Err(err),
+            },
+            WordleWorkerInput::MultiBoard { boards, guesses } => {
+                match self.handle_multi_board(boards, guesses, id) {
+                    Ok(()) => return,
+                    Err(err) => Err(err),
                 }
             }
+            WordleWorkerInput::Simulation(input) => match self.handle_simulation(input, id) {
+                // the step is sent once it's played
+                Ok(None) => return,
+                Ok(Some(output)) => Ok(output),
+                Err(err) => Err(err),
+            },
         };
         let output = match result {
             Ok(output) => output,
@@ -120,6 +468,13 @@ impl Worker for WordleWorker {
         self.link.respond(id, output);
     }
 
+    /// The bridge went away, nobody is left to receive the results of its calculation
+    fn disconnected(&mut self, id: HandlerId) {
+        if let Some(progress) = self.jobs.borrow_mut().remove(&id) {
+            progress.cancel();
+        }
+    }
+
     fn name_of_resource() -> &'static str {
         "wordle_entropy_web.js"
     }
@@ -0,0 +1,68 @@
+// Keeps a copy of everything the app loads, so that it works offline.
+// Responses come from the network when it's available and from the cache otherwise.
+const CACHE_NAME = 'wordle-entropy-v2';
+
+// generated by the Makefile, the scripts of the rayon workers are under a path changing with every build
+try {
+  importScripts('worker_snippets.js');
+} catch (err) {
+  console.warn('Worker scripts not listed, they will be cached once loaded', err);
+}
+
+// precached on install, as the page and its workers load them before the service worker takes over
+const APP_SHELL = [
+  './',
+  'index.html',
+  'wordle_entropy_web.js',
+  'wordle_entropy_web_bg.wasm',
+  'module-workers-polyfill.min.js',
+  'css/main.css',
+  'css/spectre.min.css',
+  'css/spectre-exp.min.css',
+  'css/spectre-icons.min.css',
+  'data/default_word_sets.json',
+  'data/english_words_with_probs.csv',
+  'data/polish_words_with_probs.csv',
+  ...(self.WORKER_SNIPPETS || []),
+];
+
+self.addEventListener('install', (event) => {
+  event.waitUntil(
+    caches.open(CACHE_NAME).then((cache) => cache.addAll(APP_SHELL)).then(() => self.skipWaiting())
+  );
+});
+
+self.addEventListener('activate', (event) => {
+  event.waitUntil(
+    caches.keys()
+      .then((names) => Promise.all(
+        names.filter((name) => name !== CACHE_NAME).map((name) => caches.delete(name))
+      ))
+      .then(() => self.clients.claim())
+  );
+});
+
+self.addEventListener('fetch', (event) => {
+  const request = event.request;
+  if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+    return;
+  }
+
+  event.respondWith(
+    fetch(request)
+      .then((response) => {
+        if (response.ok) {
+          const copy = response.clone();
+          caches.open(CACHE_NAME).then((cache) => cache.put(request, copy));
+        }
+        return response;
+      })
+      .catch(() => caches.match(request).then((cached) => {
+        // every route of the app is served by index.html
+        if (!cached && request.mode === 'navigate') {
+          return caches.match('index.html');
+        }
+        return cached;
+      }))
+  );
+});